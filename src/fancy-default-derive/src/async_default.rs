@@ -0,0 +1,163 @@
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::{parse2, DeriveInput, Fields};
+
+use crate::crate_path::CrateRoot;
+use crate::default::{enum_impl, struct_impl::FieldConfig};
+
+pub(crate) fn process_async_default(input: TokenStream) -> syn::Result<TokenStream> {
+    let input: DeriveInput = parse2(input)?;
+    enum_impl::expand_cfg_variants(&input, impl_async_default)
+}
+
+pub(crate) fn impl_async_default(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+    let root = CrateRoot::of(input)?;
+    let fancy_default = root.path()?;
+
+    let body = match &input.data {
+        syn::Data::Struct(struct_data) => {
+            impl_fields(&struct_data.fields, quote! { #name }, &root)?
+        }
+        syn::Data::Enum(enum_data) => {
+            let default = enum_impl::select_default_variant(&input.attrs, enum_data)?;
+            match &default.expr {
+                Some(expr) => quote! { #expr },
+                None => {
+                    let variant_name = &default.variant.ident;
+                    impl_fields(&default.variant.fields, quote! { #name::#variant_name }, &root)?
+                }
+            }
+        }
+        syn::Data::Union(_) => {
+            return Err(syn::Error::new(
+                Span::call_site(),
+                "`AsyncDefault` is not supported on unions",
+            ))
+        }
+    };
+
+    let (impl_g, type_g, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_g #fancy_default::traits::AsyncDefault for #name #type_g #where_clause {
+            fn default_async() -> impl ::core::future::Future<Output = Self> {
+                async {
+                    #body
+                }
+            }
+        }
+    })
+}
+
+/// Generate the body of the `default_async` future.
+///
+/// Synchronous fields are evaluated first, in declaration order,
+/// and then all asynchronous fields are awaited concurrently.
+fn impl_fields(fields: &Fields, path: TokenStream, root: &CrateRoot) -> syn::Result<TokenStream> {
+    let fancy_default = root.path()?;
+    let named = matches!(fields, Fields::Named(_));
+    let fields = fields
+        .iter()
+        .enumerate()
+        .map(|(idx, field)| {
+            let ident = field
+                .ident
+                .clone()
+                .unwrap_or_else(|| format_ident!("field_{}", idx));
+            let config = FieldConfig::parse_attr(&field.attrs, root)?;
+            if config.required {
+                return Err(syn::Error::new_spanned(
+                    field,
+                    "`required` fields are not supported by `AsyncDefault`",
+                ));
+            }
+            Ok((ident, &field.ty, config))
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    let values = fields
+        .iter()
+        .filter(|(_, _, config)| config.async_expr.is_none())
+        .map(|(ident, ty, config)| {
+            let expr = config.default_value(ty);
+            let cfg = &config.cfg_attrs;
+            quote! {
+                #(#cfg)*
+                let #ident: #ty = #expr;
+            }
+        });
+
+    let futures = fields
+        .iter()
+        .filter_map(|(ident, ty, config)| Some((ident, ty, config, config.async_expr.as_ref()?)))
+        .collect::<Vec<_>>();
+    let future = futures
+        .iter()
+        .map(|(ident, _, _, _)| format_ident!("__{}_future", ident))
+        .collect::<Vec<_>>();
+    let future_expr = futures.iter().map(|(_, _, _, expr)| expr);
+    let future_cfg = futures
+        .iter()
+        .map(|(_, _, config, _)| &config.cfg_attrs)
+        .collect::<Vec<_>>();
+    let awaited = futures.iter().zip(&future).map(|((ident, ty, config, _), future)| {
+        let value = config.default_value_from(ty, quote! { #future.as_mut().take() });
+        let cfg = &config.cfg_attrs;
+        quote! {
+            #(#cfg)*
+            let #ident: #ty = #value;
+        }
+    });
+
+    let join = if future.is_empty() {
+        TokenStream::new()
+    } else {
+        quote! {
+            #(
+                #(#future_cfg)*
+                let mut #future = ::core::pin::pin!(
+                    #fancy_default::__private::MaybeDone::Pending(async { #future_expr })
+                );
+            )*
+            ::core::future::poll_fn(|cx| {
+                let mut done = true;
+                #(
+                    #(#future_cfg)*
+                    {
+                        done &= #future.as_mut().poll_done(cx);
+                    }
+                )*
+                if done {
+                    ::core::task::Poll::Ready(())
+                } else {
+                    ::core::task::Poll::Pending
+                }
+            })
+            .await;
+            #(#awaited)*
+        }
+    };
+
+    let idents = fields
+        .iter()
+        .map(|(ident, _, config)| {
+            let cfg = &config.cfg_attrs;
+            quote! { #(#cfg)* #ident }
+        })
+        .collect::<Vec<_>>();
+    let construct = if named {
+        quote! { #path { #(#idents,)* } }
+    } else if idents.is_empty() {
+        path
+    } else {
+        quote! { #path(#(#idents),*) }
+    };
+
+    Ok(quote! {
+        #(#values)*
+        #join
+        #construct
+    })
+}
@@ -0,0 +1,381 @@
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::{
+    braced,
+    parse::{Parse, ParseStream, Parser},
+    parse2, parse_quote,
+    punctuated::{Pair, Punctuated},
+    token, Attribute, Data, DataStruct, DeriveInput, Expr, Field, FieldMutability, Fields,
+    FieldsNamed, Generics, Ident, LitStr, Path, Token, Type, Visibility,
+};
+
+use crate::{
+    async_default, builder,
+    crate_path::CrateRoot,
+    default::{self, enum_impl, struct_impl::FieldConfig},
+    default_with, diff, merge, overrides, partial, schema, serde, try_default, versioned,
+};
+
+pub(crate) fn process_defaults(attr: TokenStream, item: TokenStream) -> syn::Result<TokenStream> {
+    let traits = Punctuated::<Ident, Token![,]>::parse_terminated.parse2(attr)?;
+    let input: DeriveInput = parse2(item)?;
+    Ok(rewrite_item(traits.iter().collect(), input))
+}
+
+pub(crate) fn process_structure(input: TokenStream) -> syn::Result<TokenStream> {
+    let items = parse2::<InlineStructs>(input)?;
+
+    let mut expanded = TokenStream::new();
+    for mut item in items.0 {
+        let mut traits = Vec::new();
+        for attr in item.attrs.iter().filter(|a| a.path().is_ident("defaults")) {
+            if let syn::Meta::List(list) = &attr.meta {
+                traits.extend(list.parse_args_with(Punctuated::<Ident, Token![,]>::parse_terminated)?);
+            }
+        }
+        item.attrs.retain(|a| !a.path().is_ident("defaults"));
+        expanded.extend(rewrite_item(traits.iter().collect(), item.into_derive_input()));
+    }
+
+    Ok(expanded)
+}
+
+/// A sequence of structs whose fields may be written as `name: Type = <expr>`.
+struct InlineStructs(Vec<InlineStruct>);
+
+struct InlineStruct {
+    attrs: Vec<Attribute>,
+    vis: Visibility,
+    struct_token: Token![struct],
+    ident: Ident,
+    generics: Generics,
+    brace_token: token::Brace,
+    fields: Punctuated<InlineField, Token![,]>,
+}
+
+struct InlineField {
+    attrs: Vec<Attribute>,
+    vis: Visibility,
+    ident: Ident,
+    colon_token: Token![:],
+    ty: Type,
+    default: Option<Expr>,
+}
+
+impl Parse for InlineStructs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut items = Vec::new();
+        while !input.is_empty() {
+            items.push(input.parse()?);
+        }
+        Ok(InlineStructs(items))
+    }
+}
+
+impl Parse for InlineStruct {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let attrs = input.call(Attribute::parse_outer)?;
+        let vis = input.parse()?;
+        let struct_token = input.parse()?;
+        let ident = input.parse()?;
+        let mut generics: Generics = input.parse()?;
+        generics.where_clause = input.parse()?;
+
+        let content;
+        let brace_token = braced!(content in input);
+        let fields = content.parse_terminated(InlineField::parse, Token![,])?;
+
+        Ok(InlineStruct {
+            attrs,
+            vis,
+            struct_token,
+            ident,
+            generics,
+            brace_token,
+            fields,
+        })
+    }
+}
+
+impl Parse for InlineField {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let attrs = input.call(Attribute::parse_outer)?;
+        let vis = input.parse()?;
+        let ident = input.parse()?;
+        let colon_token = input.parse()?;
+        let ty = input.parse()?;
+        let default = if input.peek(Token![=]) {
+            input.parse::<Token![=]>()?;
+            Some(input.parse()?)
+        } else {
+            None
+        };
+
+        Ok(InlineField {
+            attrs,
+            vis,
+            ident,
+            colon_token,
+            ty,
+            default,
+        })
+    }
+}
+
+impl InlineStruct {
+    /// Convert the inline defaults into `#[default(expr = ...)]` attributes.
+    fn into_derive_input(self) -> DeriveInput {
+        let named = self
+            .fields
+            .into_pairs()
+            .map(|pair| {
+                let (field, punct) = pair.into_tuple();
+                let mut attrs = field.attrs;
+                if let Some(expr) = field.default {
+                    attrs.push(parse_quote!(#[default(expr = #expr)]));
+                }
+
+                let field = Field {
+                    attrs,
+                    vis: field.vis,
+                    mutability: FieldMutability::None,
+                    ident: Some(field.ident),
+                    colon_token: Some(field.colon_token),
+                    ty: field.ty,
+                };
+                Pair::new(field, punct)
+            })
+            .collect();
+
+        DeriveInput {
+            attrs: self.attrs,
+            vis: self.vis,
+            ident: self.ident,
+            generics: self.generics,
+            data: Data::Struct(DataStruct {
+                struct_token: self.struct_token,
+                fields: Fields::Named(FieldsNamed {
+                    brace_token: self.brace_token,
+                    named,
+                }),
+                semi_token: None,
+            }),
+        }
+    }
+}
+
+/// Emit the requested impls for `input`, followed by the item itself
+/// with the helper attributes replaced by documentation.
+///
+/// Unlike derive inputs, the item is not configured yet,
+/// so the generators copy the `#[cfg]` attributes of fields and variants onto their output.
+pub(crate) fn rewrite_item(traits: Vec<&Ident>, mut input: DeriveInput) -> TokenStream {
+    let default_trait: Ident = parse_quote!(Default);
+    let traits = if traits.is_empty() {
+        vec![&default_trait]
+    } else {
+        traits
+    };
+
+    // `new` and the other shared inherent items must only be generated once
+    if matches!(input.data, Data::Struct(_))
+        && traits.iter().any(|t| *t == "Default")
+        && traits.iter().any(|t| *t == "ConstDefault")
+    {
+        input.attrs.push(parse_quote!(#[default(const)]));
+    }
+
+    let mut expanded = TokenStream::new();
+    if let Err(e) = check_tuple_cfg(&input) {
+        expanded.extend(e.to_compile_error());
+    }
+
+    for t in traits {
+        let result = match t.to_string().as_str() {
+            "Default" => enum_impl::expand_cfg_variants(&input, default::impl_default),
+            "ConstDefault" => enum_impl::expand_cfg_variants(&input, default::impl_const_default),
+            "DefaultSchema" => enum_impl::expand_cfg_variants(&input, schema::impl_default_schema),
+            "DefaultBuilder" => builder::impl_default_builder(&input),
+            "TryDefault" => enum_impl::expand_cfg_variants(&input, try_default::impl_try_default),
+            "DefaultWith" => enum_impl::expand_cfg_variants(&input, default_with::impl_default_with),
+            "AsyncDefault" => {
+                enum_impl::expand_cfg_variants(&input, async_default::impl_async_default)
+            }
+            "DefaultPartial" => partial::impl_default_partial(&input),
+            "MergeDefaults" => merge::impl_merge_defaults(&input),
+            "DiffDefault" => diff::impl_diff_default(&input),
+            "DebugNonDefault" => diff::impl_debug_non_default(&input),
+            "Deserialize" => serde::impl_deserialize(&input),
+            "Serialize" => serde::impl_serialize(&input),
+            "JsonSchemaDefaults" => {
+                enum_impl::expand_cfg_variants(&input, serde::impl_json_schema_defaults)
+            }
+            "ApplyOverrides" => overrides::impl_apply_overrides(&input),
+            "VersionedDefault" => versioned::impl_versioned_default(&input),
+            _ => Err(syn::Error::new(
+                t.span(),
+                format!("`{}` cannot be generated by `#[defaults]`", t),
+            )),
+        };
+        expanded.extend(result.unwrap_or_else(|e| e.to_compile_error()));
+    }
+
+    if let Err(e) = document_item(&mut input) {
+        expanded.extend(e.to_compile_error());
+    }
+    match later_derives_read_helpers(&input) {
+        Ok(true) => {}
+        Ok(false) => strip_item_helpers(&mut input),
+        Err(e) => expanded.extend(e.to_compile_error()),
+    }
+
+    quote! {
+        #input
+        #expanded
+    }
+}
+
+/// Reject `#[cfg]` on the fields of tuple structs,
+/// since gating a field would shift the indices of the following ones.
+fn check_tuple_cfg(input: &DeriveInput) -> syn::Result<()> {
+    if let Data::Struct(DataStruct {
+        fields: Fields::Unnamed(fields),
+        ..
+    }) = &input.data
+    {
+        let cfg = fields
+            .unnamed
+            .iter()
+            .flat_map(|field| &field.attrs)
+            .find(|attr| attr.path().is_ident("cfg"));
+        if let Some(cfg) = cfg {
+            return Err(syn::Error::new_spanned(
+                cfg,
+                "`#[cfg]` on the fields of tuple structs is not supported by `#[defaults]`",
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// The derive macros that read the `#[default]` helper attributes.
+const HELPER_DERIVES: &[&str] = &[
+    "Default",
+    "ConstDefault",
+    "VariantDefault",
+    "DefaultSchema",
+    "DefaultBuilder",
+    "TryDefault",
+    "DefaultWith",
+    "AsyncDefault",
+    "DefaultPartial",
+    "MergeDefaults",
+    "DiffDefault",
+    "DebugNonDefault",
+    "Deserialize",
+    "Serialize",
+    "JsonSchemaDefaults",
+    "ApplyOverrides",
+    "VersionedDefault",
+];
+
+/// Whether a derive below `#[defaults]` reads the `#[default]` helper attributes,
+/// which are then kept on the item for it.
+fn later_derives_read_helpers(input: &DeriveInput) -> syn::Result<bool> {
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("derive")) {
+        let paths = attr.parse_args_with(Punctuated::<Path, Token![,]>::parse_terminated)?;
+        let reads_helpers = paths.iter().any(|path| {
+            path.segments
+                .last()
+                .is_some_and(|segment| HELPER_DERIVES.iter().any(|d| segment.ident == d))
+        });
+        if reads_helpers {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+fn document_item(input: &mut DeriveInput) -> syn::Result<()> {
+    match &mut input.data {
+        syn::Data::Struct(struct_data) => {
+            document_fields(&mut struct_data.fields)?;
+        }
+        syn::Data::Enum(enum_data) => {
+            let default_variant = enum_impl::select_default_variant(&input.attrs, enum_data)
+                .ok()
+                .map(|v| v.variant.ident.clone());
+
+            for variant in &mut enum_data.variants {
+                let cfg = enum_impl::variant_cfg(variant)?;
+                if let Some(predicate) = cfg {
+                    append_doc(
+                        &mut variant.attrs,
+                        &format!("This is the default variant if `cfg({})`.", predicate),
+                    );
+                } else if default_variant.as_ref() == Some(&variant.ident) {
+                    append_doc(&mut variant.attrs, "This is the default variant.");
+                }
+                document_fields(&mut variant.fields)?;
+            }
+        }
+        syn::Data::Union(_) => {
+            return Err(syn::Error::new(
+                Span::call_site(),
+                "`#[defaults]` is not supported on unions",
+            ))
+        }
+    }
+
+    Ok(())
+}
+
+fn document_fields(fields: &mut Fields) -> syn::Result<()> {
+    for field in fields.iter_mut() {
+        // the documentation does not refer to the crate, so its path does not matter here
+        let config = FieldConfig::parse_attr(&field.attrs, &CrateRoot::default())?;
+        if config.required {
+            append_doc(&mut field.attrs, "Required.");
+        } else {
+            append_doc(
+                &mut field.attrs,
+                &format!("Default: `{}`", config.expr_string()),
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Remove the `#[default]` helper attributes from the item, its variants and fields.
+fn strip_item_helpers(input: &mut DeriveInput) {
+    strip_helper_attrs(&mut input.attrs);
+    match &mut input.data {
+        Data::Struct(data) => data
+            .fields
+            .iter_mut()
+            .for_each(|field| strip_helper_attrs(&mut field.attrs)),
+        Data::Enum(data) => {
+            for variant in &mut data.variants {
+                strip_helper_attrs(&mut variant.attrs);
+                variant
+                    .fields
+                    .iter_mut()
+                    .for_each(|field| strip_helper_attrs(&mut field.attrs));
+            }
+        }
+        Data::Union(_) => {}
+    }
+}
+
+fn strip_helper_attrs(attrs: &mut Vec<Attribute>) {
+    attrs.retain(|a| !a.path().is_ident("default"));
+}
+
+fn append_doc(attrs: &mut Vec<Attribute>, doc: &str) {
+    if attrs.iter().any(|a| a.path().is_ident("doc")) {
+        attrs.push(parse_quote!(#[doc = ""]));
+    }
+    let doc = LitStr::new(doc, Span::call_site());
+    attrs.push(parse_quote!(#[doc = #doc]));
+}
@@ -0,0 +1,305 @@
+use case::CaseExt;
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::{ext::IdentExt, parse2, parse_quote, DeriveInput, Fields, GenericParam, Type};
+
+use crate::crate_path::CrateRoot;
+use crate::default::{
+    enum_impl,
+    struct_impl::{cfg_attrs, FieldConfig},
+};
+
+pub(crate) fn process_default_builder(input: TokenStream) -> syn::Result<TokenStream> {
+    let input: DeriveInput = parse2(input)?;
+    impl_default_builder(&input)
+}
+
+pub(crate) fn impl_default_builder(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+    let root = CrateRoot::of(input)?;
+    match &input.data {
+        syn::Data::Struct(struct_data) => {
+            let builder_name = format_ident!("{}Builder", name);
+            impl_builder(
+                input,
+                &struct_data.fields,
+                quote! { #name },
+                &builder_name,
+                &format_ident!("builder", span = Span::call_site()),
+                &root,
+            )
+        }
+        syn::Data::Enum(enum_data) => {
+            let mut expanded = TokenStream::new();
+            for variant in &enum_data.variants {
+                let variant_name = &variant.ident;
+                let builder_name = format_ident!("{}{}Builder", name, variant_name);
+                let fn_name = format_ident!("{}_builder", variant_name.unraw().to_string().to_snake());
+                let builder = impl_builder(
+                    input,
+                    &variant.fields,
+                    quote! { #name::#variant_name },
+                    &builder_name,
+                    &fn_name,
+                    &root,
+                )?;
+                expanded.extend(enum_impl::gate_items(&cfg_attrs(&variant.attrs), builder)?);
+            }
+            Ok(expanded)
+        }
+        syn::Data::Union(_) => Err(syn::Error::new(
+            Span::call_site(),
+            "`DefaultBuilder` is not supported on unions",
+        )),
+    }
+}
+
+struct BuilderField<'a> {
+    /// The binding, which is also the name of the setter.
+    ident: Ident,
+    ty: &'a Type,
+    config: FieldConfig,
+    /// The typestate parameter, for required fields.
+    state: Option<Ident>,
+}
+
+fn impl_builder(
+    input: &DeriveInput,
+    fields: &Fields,
+    path: TokenStream,
+    builder_name: &Ident,
+    fn_name: &Ident,
+    root: &CrateRoot,
+) -> syn::Result<TokenStream> {
+    let (vis, generics, name) = (&input.vis, &input.generics, &input.ident);
+    let named = matches!(fields, Fields::Named(_));
+    let fields = fields
+        .iter()
+        .enumerate()
+        .map(|(idx, field)| {
+            let ident = field
+                .ident
+                .clone()
+                .unwrap_or_else(|| format_ident!("field_{}", idx));
+            let config = FieldConfig::parse_attr(&field.attrs, root)?;
+            if config.required && !config.cfg_attrs.is_empty() {
+                // the typestate arguments of the builder cannot be gated
+                return Err(syn::Error::new_spanned(
+                    field,
+                    "`#[cfg]` on `required` fields is not supported by `DefaultBuilder`",
+                ));
+            }
+            let state = config
+                .required
+                .then(|| format_ident!("__{}", ident.unraw().to_string().to_camel()));
+            Ok(BuilderField {
+                ident,
+                ty: &field.ty,
+                config,
+                state,
+            })
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    let (impl_g, type_g, where_clause) = generics.split_for_impl();
+    let type_args = generics.params.iter().map(|param| match param {
+        GenericParam::Lifetime(l) => {
+            let lifetime = &l.lifetime;
+            quote! { #lifetime }
+        }
+        GenericParam::Type(t) => {
+            let ident = &t.ident;
+            quote! { #ident }
+        }
+        GenericParam::Const(c) => {
+            let ident = &c.ident;
+            quote! { #ident }
+        }
+    });
+    let type_args = quote! { #(#type_args,)* };
+
+    let states = fields.iter().filter_map(|f| f.state.as_ref()).collect::<Vec<_>>();
+    // only the typestate of the required fields needs the crate
+    let fancy_default = if states.is_empty() {
+        TokenStream::new()
+    } else {
+        root.path()?
+    };
+    let unset = states
+        .iter()
+        .map(|_| quote! { #fancy_default::builder::Unset })
+        .collect::<Vec<_>>();
+
+    let storage = fields.iter().map(|f| {
+        let BuilderField {
+            ident,
+            ty,
+            config,
+            state,
+        } = f;
+        let cfg = &config.cfg_attrs;
+        match state {
+            Some(state) => quote! { #ident: #state },
+            None => quote! { #(#cfg)* #ident: ::core::option::Option<#ty> },
+        }
+    });
+
+    // the builder struct, with the typestate parameters appended to the original generics
+    let mut builder_generics = generics.clone();
+    for state in &states {
+        builder_generics
+            .params
+            .push(parse_quote!(#state = #fancy_default::builder::Unset));
+    }
+    let builder_params = &builder_generics.params;
+
+    let initial = fields.iter().map(|f| {
+        let ident = &f.ident;
+        let cfg = &f.config.cfg_attrs;
+        match f.state {
+            Some(_) => quote! { #ident: #fancy_default::builder::Unset },
+            None => quote! { #(#cfg)* #ident: ::core::option::Option::None },
+        }
+    });
+
+    // setters available in any state
+    let mut any_state = generics.clone();
+    for state in &states {
+        any_state.params.push(parse_quote!(#state));
+    }
+    let (any_impl_g, _, _) = any_state.split_for_impl();
+
+    let optional_setters = fields.iter().filter(|f| f.state.is_none()).map(|f| {
+        let BuilderField {
+            ident, ty, config, ..
+        } = f;
+        let cfg = &config.cfg_attrs;
+        quote! {
+            #(#cfg)*
+            #vis fn #ident(mut self, value: #ty) -> Self {
+                self.#ident = ::core::option::Option::Some(value);
+                self
+            }
+        }
+    });
+
+    let required_setters = fields.iter().filter(|f| f.state.is_some()).map(|f| {
+        let BuilderField {
+            ident, ty, state, ..
+        } = f;
+
+        let mut setter_generics = generics.clone();
+        for other in states.iter().filter(|s| Some(**s) != state.as_ref()) {
+            setter_generics.params.push(parse_quote!(#other));
+        }
+        let (setter_impl_g, _, _) = setter_generics.split_for_impl();
+
+        let state_args = |this: TokenStream| {
+            let args = states.iter().map(|s| {
+                if Some(*s) == state.as_ref() {
+                    this.clone()
+                } else {
+                    quote! { #s }
+                }
+            });
+            quote! { #(#args),* }
+        };
+        let from = state_args(quote! { #fancy_default::builder::Unset });
+        let to = state_args(quote! { #fancy_default::builder::Set<#ty> });
+
+        let moved = fields.iter().map(|other| {
+            let other_ident = &other.ident;
+            let cfg = &other.config.cfg_attrs;
+            if other_ident == ident {
+                quote! { #ident: #fancy_default::builder::Set(value) }
+            } else {
+                quote! { #(#cfg)* #other_ident: self.#other_ident }
+            }
+        });
+
+        quote! {
+            impl #setter_impl_g #builder_name<#type_args #from> #where_clause {
+                #vis fn #ident(self, value: #ty) -> #builder_name<#type_args #to> {
+                    #builder_name {
+                        #(#moved,)*
+                        __marker: ::core::marker::PhantomData,
+                    }
+                }
+            }
+        }
+    });
+
+    let set = fields
+        .iter()
+        .filter_map(|f| {
+            let ty = f.ty;
+            f.state
+                .as_ref()
+                .map(|_| quote! { #fancy_default::builder::Set<#ty> })
+        })
+        .collect::<Vec<_>>();
+
+    let values = fields.iter().map(|f| {
+        let BuilderField {
+            ident, ty, config, ..
+        } = f;
+        if f.state.is_some() {
+            quote! { let #ident: #ty = self.#ident.0; }
+        } else {
+            let expr = config.default_value(ty);
+            let cfg = &config.cfg_attrs;
+            quote! {
+                #(#cfg)*
+                let #ident: #ty = match self.#ident {
+                    ::core::option::Option::Some(value) => value,
+                    ::core::option::Option::None => #expr,
+                };
+            }
+        }
+    });
+
+    let idents = fields
+        .iter()
+        .map(|f| {
+            let ident = &f.ident;
+            let cfg = &f.config.cfg_attrs;
+            quote! { #(#cfg)* #ident }
+        })
+        .collect::<Vec<_>>();
+    let construct = if named {
+        quote! { #path { #(#idents,)* } }
+    } else if idents.is_empty() {
+        path
+    } else {
+        quote! { #path(#(#idents),*) }
+    };
+
+    Ok(quote! {
+        #vis struct #builder_name<#builder_params> #where_clause {
+            #(#storage,)*
+            __marker: ::core::marker::PhantomData<fn() -> #name #type_g>,
+        }
+
+        impl #impl_g #name #type_g #where_clause {
+            #vis fn #fn_name() -> #builder_name<#type_args #(#unset),*> {
+                #builder_name {
+                    #(#initial,)*
+                    __marker: ::core::marker::PhantomData,
+                }
+            }
+        }
+
+        impl #any_impl_g #builder_name<#type_args #(#states),*> #where_clause {
+            #(#optional_setters)*
+        }
+
+        #(#required_setters)*
+
+        impl #impl_g #builder_name<#type_args #(#set),*> #where_clause {
+            #vis fn build(self) -> #name #type_g {
+                #(#values)*
+                #construct
+            }
+        }
+    })
+}
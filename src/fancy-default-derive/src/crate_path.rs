@@ -0,0 +1,64 @@
+use proc_macro2::{Span, TokenStream};
+use quote::{quote, ToTokens};
+use syn::{parse::ParseStream, DeriveInput, LitStr, Path};
+
+use crate::default::{enum_impl::EnumConfig, struct_impl::StructConfig};
+
+/// Where the generated code finds the items of `fancy_default`.
+#[derive(Clone)]
+pub(crate) enum CratePath {
+    /// `#[default(crate = ...)]`: the crate is renamed or re-exported under this path.
+    Path(Path),
+    /// `#[default(core)]`: only `core` is used, limiting the derives to what `core` provides.
+    Core,
+}
+
+impl CratePath {
+    /// Parse the value of `crate = ...`, either a path or a string literal holding one.
+    ///
+    /// Only the bare path can carry the hygiene of `$crate` from a `macro_rules!` macro.
+    pub(crate) fn parse(input: ParseStream) -> syn::Result<CratePath> {
+        if input.peek(LitStr) {
+            let lit: LitStr = input.parse()?;
+            Ok(CratePath::Path(lit.parse()?))
+        } else {
+            Ok(CratePath::Path(input.call(Path::parse_mod_style)?))
+        }
+    }
+}
+
+/// The crate path configured on an item, used to build the paths of the generated code.
+#[derive(Clone, Default)]
+pub(crate) struct CrateRoot(Option<CratePath>);
+
+impl CrateRoot {
+    /// The crate root configured on `input`.
+    pub(crate) fn of(input: &DeriveInput) -> syn::Result<CrateRoot> {
+        Ok(CrateRoot(match &input.data {
+            syn::Data::Struct(_) => StructConfig::parse_attr(&input.attrs)?.crate_path,
+            syn::Data::Enum(_) => EnumConfig::parse_attr(&input.attrs)?.crate_path,
+            syn::Data::Union(_) => None,
+        }))
+    }
+
+    /// The path to the `fancy_default` crate, `::fancy_default` unless configured.
+    pub(crate) fn path(&self) -> syn::Result<TokenStream> {
+        match &self.0 {
+            None => Ok(quote! { ::fancy_default }),
+            Some(CratePath::Path(path)) => Ok(path.to_token_stream()),
+            Some(CratePath::Core) => Err(syn::Error::new(
+                Span::call_site(),
+                "this requires the `fancy_default` crate, which is not used with `#[default(core)]`",
+            )),
+        }
+    }
+
+    /// The `Default` trait, which is the one of `core` with `#[default(core)]`.
+    pub(crate) fn default_trait(&self) -> TokenStream {
+        match &self.0 {
+            None => quote! { ::fancy_default::traits::Default },
+            Some(CratePath::Path(path)) => quote! { #path::traits::Default },
+            Some(CratePath::Core) => quote! { ::core::default::Default },
+        }
+    }
+}
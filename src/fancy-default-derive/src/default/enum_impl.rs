@@ -1,57 +1,81 @@
 use itertools::Itertools;
 use proc_macro2::{Ident, Span, TokenStream};
-use quote::{format_ident, quote};
+use quote::{format_ident, quote, ToTokens};
 use syn::{
-    parse2, spanned::Spanned, Attribute, DataEnum, Expr, ExprLit, Fields, FieldsNamed,
-    FieldsUnnamed, Generics, Lit, LitBool, Meta, MetaNameValue, Token, Type, Variant,
+    parenthesized, parse2, parse_quote, spanned::Spanned, Attribute, DataEnum, DeriveInput, Expr,
+    ExprLit, Field, Fields, FieldsNamed, FieldsUnnamed, File, Generics, Lit, LitBool, Meta,
+    MetaNameValue, Token, Type, Variant,
 };
 
+use crate::crate_path::{CratePath, CrateRoot};
+
+use super::struct_impl::{cfg_attrs, gated_predicates, FieldConfig};
+
 pub(crate) fn impl_enum_const(
+    attrs: &[Attribute],
     data: &DataEnum,
     name: &Ident,
     generics: &Generics,
+    root: &CrateRoot,
 ) -> syn::Result<TokenStream> {
-    let configs = data
-        .variants
-        .iter()
-        .map(|v| VariantConfig::parse_attr(&v.attrs).map(|c| (v, c)))
-        .filter(|v| v.is_err() || v.as_ref().is_ok_and(|(_, c)| c.default))
-        .collect::<Result<Vec<_>, _>>()?;
-
-    let variant = if let Some((var, _)) = configs.first() {
-        var
-    } else if configs.len() > 1 {
-        let mut err = syn::Error::new(Span::call_site(), "more than one variant are set `default`");
-        configs.iter().for_each(|(v, _)| {
-            err.combine(syn::Error::new(
-                v.ident.span(),
-                "more than one variant are set `default`",
-            ))
-        });
-        return Err(err);
-    } else {
-        return Err(syn::Error::new(
-            Span::call_site(),
-            "at least one variant should be set `default`",
-        ));
-    };
-
-    let block = impl_enum_variant(variant, name)?;
+    let block = select_default_variant(attrs, data)?.to_block(name, root)?;
     let (impl_g, type_g, where_clause) = generics.split_for_impl();
 
+    let fancy_default = root.path()?;
+
     Ok(quote! {
         #[automatically_derived]
-        impl #impl_g ::fancy_default::ConstDefault for #name #type_g #where_clause {
+        impl #impl_g #fancy_default::ConstDefault for #name #type_g #where_clause {
             const DEFAULT: Self = #block;
         }
     })
 }
 
 pub(crate) fn impl_enum(
+    attrs: &[Attribute],
     data: &DataEnum,
     name: &Ident,
     generics: &Generics,
+    root: &CrateRoot,
 ) -> syn::Result<TokenStream> {
+    let block = select_default_variant(attrs, data)?.to_block(name, root)?;
+    let (impl_g, type_g, where_clause) = generics.split_for_impl();
+
+    let default_trait = root.default_trait();
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_g #default_trait for #name #type_g #where_clause {
+            fn default() -> Self {
+                #block
+            }
+        }
+    })
+}
+
+/// The variant selected as the default value of an enum.
+pub(crate) struct DefaultVariant<'a> {
+    pub(crate) variant: &'a Variant,
+    /// The payload override from the enum-level `#[default(expr = ...)]`.
+    pub(crate) expr: Option<Expr>,
+}
+
+impl DefaultVariant<'_> {
+    pub(crate) fn to_block(&self, name: &Ident, root: &CrateRoot) -> syn::Result<TokenStream> {
+        match &self.expr {
+            Some(expr) => Ok(expr.to_token_stream()),
+            None => impl_enum_variant(self.variant, name, root),
+        }
+    }
+}
+
+/// Select the default variant, either from the enum-level `#[default(variant = ...)]`
+/// or from the `#[default]` attribute on a single variant.
+pub(crate) fn select_default_variant<'a>(
+    attrs: &[Attribute],
+    data: &'a DataEnum,
+) -> syn::Result<DefaultVariant<'a>> {
+    let enum_config = EnumConfig::parse_attr(attrs)?;
     let configs = data
         .variants
         .iter()
@@ -59,73 +83,211 @@ pub(crate) fn impl_enum(
         .filter(|v| v.is_err() || v.as_ref().is_ok_and(|(_, c)| c.default))
         .collect::<Result<Vec<_>, _>>()?;
 
-    let variant = if let Some((var, _)) = configs.first() {
-        var
-    } else if configs.len() > 1 {
-        let mut err = syn::Error::new(Span::call_site(), "more than one variant are set `default`");
-        configs.iter().for_each(|(v, _)| {
-            err.combine(syn::Error::new(
-                v.ident.span(),
-                "more than one variant are set `default`",
-            ))
+    if let Some(ident) = &enum_config.variant {
+        if let Some((var, _)) = configs.first() {
+            return Err(syn::Error::new(
+                var.ident.span(),
+                "the default variant is already selected by `#[default(variant = ...)]` on the enum",
+            ));
+        }
+
+        let variant = data
+            .variants
+            .iter()
+            .find(|v| v.ident == *ident)
+            .ok_or_else(|| {
+                syn::Error::new(ident.span(), format!("no variant named `{}`", ident))
+            })?;
+
+        if let Some(expr) = &enum_config.expr {
+            check_variant_expr(expr, ident, data)?;
+        }
+
+        return Ok(DefaultVariant {
+            variant,
+            expr: enum_config.expr,
         });
-        return Err(err);
-    } else {
+    }
+
+    if let Some(expr) = &enum_config.expr {
         return Err(syn::Error::new(
+            expr.span(),
+            "`expr` on the enum requires `variant` to be set",
+        ));
+    }
+
+    match configs.as_slice() {
+        [(variant, _)] => Ok(DefaultVariant {
+            variant,
+            expr: None,
+        }),
+        [] => Err(syn::Error::new(
             Span::call_site(),
             "at least one variant should be set `default`",
-        ));
-    };
+        )),
+        _ => {
+            let mut err =
+                syn::Error::new(Span::call_site(), "more than one variant are set `default`");
+            configs.iter().for_each(|(v, _)| {
+                err.combine(syn::Error::new(
+                    v.ident.span(),
+                    "more than one variant are set `default`",
+                ))
+            });
+            Err(err)
+        }
+    }
+}
 
-    let block = impl_enum_variant(variant, name)?;
-    let (impl_g, type_g, where_clause) = generics.split_for_impl();
+/// Check that the enum-level `expr` does not construct another variant than the selected one,
+/// so that the reported default variant is the one actually returned.
+///
+/// Only paths, calls and struct expressions ending in a variant name are checked.
+/// Other expressions, like constants or helper functions, are trusted.
+fn check_variant_expr(expr: &Expr, variant: &Ident, data: &DataEnum) -> syn::Result<()> {
+    let path = match expr {
+        Expr::Path(expr) => Some(&expr.path),
+        Expr::Call(call) => match &*call.func {
+            Expr::Path(func) => Some(&func.path),
+            _ => None,
+        },
+        Expr::Struct(expr) => Some(&expr.path),
+        _ => None,
+    };
 
-    Ok(quote! {
-        #[automatically_derived]
-        impl #impl_g ::fancy_default::traits::Default for #name #type_g #where_clause {
-            fn default() -> Self {
-                #block
-            }
+    match path.and_then(|path| path.segments.last()) {
+        Some(segment)
+            if segment.ident != *variant
+                && data.variants.iter().any(|v| v.ident == segment.ident) =>
+        {
+            Err(syn::Error::new(
+                segment.ident.span(),
+                format!(
+                    "`expr` on the enum constructs `{}`, but the selected variant is `{}`",
+                    segment.ident, variant
+                ),
+            ))
         }
-    })
+        _ => Ok(()),
+    }
 }
 
-#[derive(Clone)]
-#[non_exhaustive]
-struct VariantFieldConfig {
-    expr: Expr,
+/// The `cfg` predicate of a variant selected by `#[default(cfg(...))]`.
+pub(crate) fn variant_cfg(variant: &Variant) -> syn::Result<Option<TokenStream>> {
+    VariantConfig::parse_attr(&variant.attrs).map(|config| config.cfg)
 }
 
-impl VariantFieldConfig {
-    fn parse_attr(attrs: &[Attribute]) -> syn::Result<VariantFieldConfig> {
-        let mut expr: Expr = parse2(quote! {::fancy_default::traits::Default::default()})?;
+/// Expand `input` once for each variant selected by `#[default(cfg(...))]`,
+/// as if it were marked `#[default]`, with the generated items gated by its predicate.
+///
+/// The generated items are also gated by the `#[cfg]` attributes of the default variant,
+/// which are only seen by `#[defaults]`. Other inputs are expanded as is.
+pub(crate) fn expand_cfg_variants(
+    input: &DeriveInput,
+    expand: impl Fn(&DeriveInput) -> syn::Result<TokenStream>,
+) -> syn::Result<TokenStream> {
+    let syn::Data::Enum(enum_data) = &input.data else {
+        return expand(input);
+    };
+    let mut candidates = Vec::new();
+    for (idx, variant) in enum_data.variants.iter().enumerate() {
+        if let Some(predicate) = variant_cfg(variant)? {
+            candidates.push((idx, predicate));
+        }
+    }
+    if candidates.is_empty() {
+        let expanded = expand(input)?;
+        let variant = select_default_variant(&input.attrs, enum_data)?.variant;
+        let cfg = cfg_attrs(&variant.attrs);
+        if cfg.is_empty() {
+            return Ok(expanded);
+        }
 
-        for attr in attrs.iter().filter(|a| a.meta.path().is_ident("default")) {
-            match &attr.meta {
-                Meta::List(meta_list) => meta_list.parse_nested_meta(|meta| {
-                    if meta.path.is_ident("expr") {
-                        let val: Expr = meta.value()?.parse()?;
-                        expr = val;
-                        return Ok(());
-                    }
+        let predicate = cfg.iter().map(|attr| attr.meta.require_list().map(|list| &list.tokens));
+        let predicate = predicate.collect::<syn::Result<Vec<_>>>()?;
+        let message = format!(
+            "the default variant of `{}` is disabled in the current configuration",
+            input.ident
+        );
+        let mut gated = gate_items(&cfg, expanded)?;
+        gated.extend(quote! {
+            #[cfg(not(all(#(#predicate),*)))]
+            ::core::compile_error!(#message);
+        });
+        return Ok(gated);
+    }
 
-                    Err(meta.error("unrecognized default parameter"))
-                })?,
-                Meta::NameValue(nv) => expr = nv.value.clone(),
-                _ => {}
+    let predicate = candidates.iter().map(|(_, predicate)| predicate);
+    let gated = gated_predicates(predicate.clone());
+    let mut expanded = TokenStream::new();
+    for ((selected, _), gated) in candidates.iter().zip(gated) {
+        let mut input = input.clone();
+        if let syn::Data::Enum(enum_data) = &mut input.data {
+            for (idx, variant) in enum_data.variants.iter_mut().enumerate() {
+                if candidates.iter().any(|(candidate, _)| *candidate == idx) {
+                    variant.attrs.retain(|a| !a.path().is_ident("default"));
+                }
+                if idx == *selected {
+                    variant.attrs.push(parse_quote! { #[default] });
+                }
             }
         }
 
-        Ok(VariantFieldConfig { expr })
+        let variant = &enum_data.variants[*selected];
+        let mut cfg = cfg_attrs(&variant.attrs);
+        cfg.insert(0, parse_quote! { #[cfg(#gated)] });
+        expanded.extend(gate_items(&cfg, expand(&input)?)?);
+    }
+
+    let message = format!(
+        "no default variant of `{}` matches the current configuration",
+        input.ident
+    );
+    expanded.extend(quote! {
+        #[cfg(not(any(#(#predicate),*)))]
+        ::core::compile_error!(#message);
+    });
+    Ok(expanded)
+}
+
+/// Put the `#[cfg]` attributes onto each item of `tokens`.
+pub(crate) fn gate_items(cfg: &[Attribute], tokens: TokenStream) -> syn::Result<TokenStream> {
+    if cfg.is_empty() {
+        return Ok(tokens);
+    }
+
+    let file: File = parse2(tokens)?;
+    let mut gated = TokenStream::new();
+    for item in file.items {
+        gated.extend(quote! {
+            #(#cfg)*
+            #item
+        });
+    }
+    Ok(gated)
+}
+
+fn parse_variant_field(field: &Field, root: &CrateRoot) -> syn::Result<FieldConfig> {
+    let config = FieldConfig::parse_attr(&field.attrs, root)?;
+    if config.required {
+        return Err(syn::Error::new(
+            field.span(),
+            "`required` is only supported on struct fields",
+        ));
     }
+    Ok(config)
 }
 
-pub(crate) fn impl_enum_variant(variant: &Variant, name: &Ident) -> syn::Result<TokenStream> {
+pub(crate) fn impl_enum_variant(
+    variant: &Variant,
+    name: &Ident,
+    root: &CrateRoot,
+) -> syn::Result<TokenStream> {
     let fields = &variant.fields;
     let ident = &variant.ident;
     let tokens = match fields {
-        Fields::Named(field) => impl_named_struct(field, ident, name),
-        Fields::Unnamed(field) => impl_unnamed_struct(field, ident, name),
+        Fields::Named(field) => impl_named_struct(field, ident, name, root),
+        Fields::Unnamed(field) => impl_unnamed_struct(field, ident, name, root),
         Fields::Unit => Ok(quote! { #name::#ident }),
     }?;
 
@@ -136,6 +298,7 @@ fn impl_named_struct(
     fields: &FieldsNamed,
     variant_name: &Ident,
     enum_name: &Ident,
+    root: &CrateRoot,
 ) -> syn::Result<TokenStream> {
     let result = fields
         .named
@@ -147,7 +310,7 @@ fn impl_named_struct(
                     .as_ref()
                     .unwrap_or_else(|| panic!("unexpected internal error: unnamed field")),
                 &field.ty,
-                VariantFieldConfig::parse_attr(&field.attrs).map_err(|e| e.to_compile_error())?,
+                parse_variant_field(field, root).map_err(|e| e.to_compile_error())?,
             ))
         })
         .collect::<Result<Vec<_>, _>>();
@@ -157,18 +320,19 @@ fn impl_named_struct(
         Err(e) => return Ok(e),
     };
 
-    let (ident, ty, expr): (Vec<&Ident>, Vec<&Type>, Vec<Expr>) = result
+    let (ident, ty, expr, cfg): (Vec<&Ident>, Vec<&Type>, Vec<TokenStream>, Vec<_>) = result
         .into_iter()
-        .map(|(a, b, c)| (a, b, c.expr))
+        .map(|(a, b, c)| (a, b, c.default_value(b), c.cfg_attrs))
         .multiunzip();
 
     Ok(quote! {{
         #(
+            #(#cfg)*
             let #ident: #ty = #expr;
         )*
 
         #enum_name::#variant_name {
-            #(#ident,)*
+            #(#(#cfg)* #ident,)*
         }
     }})
 }
@@ -177,6 +341,7 @@ fn impl_unnamed_struct(
     fields: &FieldsUnnamed,
     variant_name: &Ident,
     enum_name: &Ident,
+    root: &CrateRoot,
 ) -> syn::Result<TokenStream> {
     let result = fields
         .unnamed
@@ -186,7 +351,7 @@ fn impl_unnamed_struct(
             Ok((
                 format_ident!("field_{}", idx),
                 &field.ty,
-                VariantFieldConfig::parse_attr(&field.attrs).map_err(|e| e.to_compile_error())?,
+                parse_variant_field(field, root).map_err(|e| e.to_compile_error())?,
             ))
         })
         .collect::<Result<Vec<_>, _>>();
@@ -196,17 +361,18 @@ fn impl_unnamed_struct(
         Err(e) => return Ok(e),
     };
 
-    let (ident, ty, expr): (Vec<Ident>, Vec<&Type>, Vec<Expr>) = result
+    let (ident, ty, expr, cfg): (Vec<Ident>, Vec<&Type>, Vec<TokenStream>, Vec<_>) = result
         .into_iter()
-        .map(|(a, b, c)| (a, b, c.expr))
+        .map(|(a, b, c)| (a, b, c.default_value(b), c.cfg_attrs))
         .multiunzip();
 
     Ok(quote! {{
         #(
+            #(#cfg)*
             let #ident: #ty = #expr;
         )*
 
-        #enum_name::#variant_name(#(#ident),*)
+        #enum_name::#variant_name(#(#(#cfg)* #ident),*)
     }})
 }
 
@@ -214,15 +380,25 @@ fn impl_unnamed_struct(
 #[non_exhaustive]
 struct VariantConfig {
     default: bool,
+    /// The `cfg` predicate under which the variant is the default one.
+    cfg: Option<TokenStream>,
 }
 
 impl VariantConfig {
     fn parse_attr(attrs: &[Attribute]) -> syn::Result<VariantConfig> {
         let mut default = false;
+        let mut cfg = None;
 
         for attr in attrs.iter().filter(|a| a.meta.path().is_ident("default")) {
             match &attr.meta {
                 Meta::List(meta_list) => meta_list.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("cfg") {
+                        let content;
+                        parenthesized!(content in meta.input);
+                        cfg = Some(content.parse()?);
+                        return Ok(());
+                    }
+
                     if meta.path.is_ident("default") {
                         if meta.input.peek(Token![=]) {
                             let val: LitBool = meta.value()?.parse()?;
@@ -256,6 +432,68 @@ impl VariantConfig {
             }
         }
 
-        Ok(VariantConfig { default })
+        Ok(VariantConfig { default, cfg })
+    }
+}
+
+#[derive(Clone, Default)]
+#[non_exhaustive]
+pub(crate) struct EnumConfig {
+    variant: Option<Ident>,
+    expr: Option<Expr>,
+    /// The context type of `DefaultWith`.
+    pub(crate) ctx: Option<Type>,
+    /// The path of the `fancy_default` crate used by the generated code.
+    pub(crate) crate_path: Option<CratePath>,
+}
+
+impl EnumConfig {
+    pub(crate) fn parse_attr(attrs: &[Attribute]) -> syn::Result<EnumConfig> {
+        let mut config = EnumConfig::default();
+
+        for attr in attrs.iter().filter(|a| a.meta.path().is_ident("default")) {
+            match &attr.meta {
+                Meta::List(meta_list) => meta_list.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("crate") {
+                        config.crate_path = Some(CratePath::parse(meta.value()?)?);
+                        return Ok(());
+                    }
+
+                    if meta.path.is_ident("core") {
+                        config.crate_path = Some(CratePath::Core);
+                        return Ok(());
+                    }
+
+                    if meta.path.is_ident("variant") {
+                        if config.variant.is_some() {
+                            return Err(meta.error("duplicate `variant` parameter"));
+                        }
+                        config.variant = Some(meta.value()?.parse()?);
+                        return Ok(());
+                    }
+
+                    if meta.path.is_ident("expr") {
+                        if config.expr.is_some() {
+                            return Err(meta.error("duplicate `expr` parameter"));
+                        }
+                        config.expr = Some(meta.value()?.parse()?);
+                        return Ok(());
+                    }
+
+                    if meta.path.is_ident("ctx") {
+                        config.ctx = Some(meta.value()?.parse()?);
+                        return Ok(());
+                    }
+
+                    Err(meta.error("unrecognized enum default parameter"))
+                })?,
+                _ => Err(syn::Error::new(
+                    attr.meta.span(),
+                    "expected `#[default(variant = <variant>)]`",
+                ))?,
+            }
+        }
+
+        Ok(config)
     }
 }
@@ -1,31 +1,63 @@
-use proc_macro2::TokenStream;
+use proc_macro2::{Span, TokenStream};
 use syn::{parse2, DeriveInput};
 
+use crate::crate_path::CrateRoot;
+
 pub(super) mod enum_impl;
 pub(super) mod struct_impl;
 
 pub(crate) fn process_default_derive(input: TokenStream) -> syn::Result<TokenStream> {
     let input: DeriveInput = parse2(input)?;
+    enum_impl::expand_cfg_variants(&input, impl_default)
+}
+
+pub(crate) fn process_const_default_derive(input: TokenStream) -> syn::Result<TokenStream> {
+    let input: DeriveInput = parse2(input)?;
+    enum_impl::expand_cfg_variants(&input, impl_const_default)
+}
+
+pub(crate) fn impl_default(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let root = CrateRoot::of(input)?;
     match &input.data {
         syn::Data::Struct(struct_data) => {
-            struct_impl::impl_struct(struct_data, &input.ident, &input.generics)
+            struct_impl::impl_struct(
+                &input.attrs,
+                &input.vis,
+                struct_data,
+                &input.ident,
+                &input.generics,
+                &root,
+            )
         }
         syn::Data::Enum(enum_data) => {
-            enum_impl::impl_enum(enum_data, &input.ident, &input.generics)
+            enum_impl::impl_enum(&input.attrs, enum_data, &input.ident, &input.generics, &root)
         }
-        _ => unimplemented!(),
+        syn::Data::Union(_) => Err(syn::Error::new(
+            Span::call_site(),
+            "`Default` is not supported on unions",
+        )),
     }
 }
 
-pub(crate) fn process_const_default_derive(input: TokenStream) -> syn::Result<TokenStream> {
-    let input: DeriveInput = parse2(input)?;
+pub(crate) fn impl_const_default(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let root = CrateRoot::of(input)?;
     match &input.data {
         syn::Data::Struct(struct_data) => {
-            struct_impl::impl_struct_const(struct_data, &input.ident, &input.generics)
+            struct_impl::impl_struct_const(
+                &input.attrs,
+                &input.vis,
+                struct_data,
+                &input.ident,
+                &input.generics,
+                &root,
+            )
         },
         syn::Data::Enum(enum_data) => {
-            enum_impl::impl_enum_const(enum_data, &input.ident, &input.generics)
+            enum_impl::impl_enum_const(&input.attrs, enum_data, &input.ident, &input.generics, &root)
         }
-        _ => unimplemented!()
+        syn::Data::Union(_) => Err(syn::Error::new(
+            Span::call_site(),
+            "`ConstDefault` is not supported on unions",
+        )),
     }
 }
@@ -1,69 +1,604 @@
+use case::CaseExt;
 use itertools::Itertools;
-use proc_macro2::{Ident, TokenStream};
-use quote::{format_ident, quote};
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::{format_ident, quote, quote_spanned, ToTokens};
 use syn::{
-    parse_quote, Attribute, DataStruct, Expr, Fields, FieldsNamed, FieldsUnnamed,
-    Generics, LitBool, Meta, Token, Type,
+    parenthesized,
+    parse::{Parse, ParseStream},
+    parse_quote,
+    punctuated::Punctuated,
+    spanned::Spanned,
+    token, Attribute, DataStruct, Expr, Fields, FieldsNamed, FieldsUnnamed, Generics, Index,
+    LitBool, LitStr, Member, Meta, Path, PathArguments, Token, Type, Visibility,
 };
 
+use crate::crate_path::{CratePath, CrateRoot};
+
 pub(crate) fn impl_struct_const(
+    attrs: &[Attribute],
+    vis: &Visibility,
     data: &DataStruct,
     name: &Ident,
     generics: &Generics,
+    root: &CrateRoot,
 ) -> syn::Result<TokenStream> {
+    let config = StructConfig::parse_attr(attrs)?;
     let block = match &data.fields {
-        Fields::Named(fields) => impl_named_struct(fields, name),
-        Fields::Unnamed(fields) => impl_unnamed_struct(fields, name),
+        Fields::Named(fields) => impl_named_struct(fields, name, root),
+        Fields::Unnamed(fields) => impl_unnamed_struct(fields, name, root),
         Fields::Unit => Ok(quote! { #name }),
     }?;
 
     let (impl_g, type_g, where_clause) = generics.split_for_impl();
+    let required = required_fields(&data.fields, root)?;
 
-    Ok(quote! {
-        #[automatically_derived]
-        impl #impl_g ::fancy_default::ConstDefault for #name #type_g #where_clause {
-            const DEFAULT: Self = #block;
+    let default_impl = if required.is_empty() {
+        let fancy_default = root.path()?;
+        quote! {
+            #[automatically_derived]
+            impl #impl_g #fancy_default::ConstDefault for #name #type_g #where_clause {
+                const DEFAULT: Self = #block;
+            }
         }
+    } else if config.constant {
+        // `new` is generated by `Default` instead
+        TokenStream::new()
+    } else {
+        impl_new(&required, &block, true, vis, name, generics)
+    };
+
+    let make = if config.constant {
+        // the macro is generated by `Default` instead
+        TokenStream::new()
+    } else {
+        impl_make(&config, data, name, root)?
+    };
+
+    let with = match &config.with {
+        // the setters are generated by `Default` instead
+        Some(_) if config.constant => TokenStream::new(),
+        Some(with) => impl_with_setters(with, vis, data, name, generics)?,
+        None => TokenStream::new(),
+    };
+
+    let explicit_fields = if config.constant {
+        // `explicit_fields` is generated by `Default` instead
+        TokenStream::new()
+    } else {
+        impl_explicit_fields(vis, data, name, generics)
+    };
+
+    Ok(quote! {
+        #default_impl
+
+        #make
+
+        #with
+
+        #explicit_fields
     })
 }
 
 pub(crate) fn impl_struct(
+    attrs: &[Attribute],
+    vis: &Visibility,
     data: &DataStruct,
     name: &Ident,
     generics: &Generics,
+    root: &CrateRoot,
 ) -> syn::Result<TokenStream> {
+    let config = StructConfig::parse_attr(attrs)?;
     let block = match &data.fields {
-        Fields::Named(fields) => impl_named_struct(fields, name),
-        Fields::Unnamed(fields) => impl_unnamed_struct(fields, name),
+        Fields::Named(fields) => impl_named_struct(fields, name, root),
+        Fields::Unnamed(fields) => impl_unnamed_struct(fields, name, root),
         Fields::Unit => Ok(quote! { #name }),
     }?;
 
     let (impl_g, type_g, where_clause) = generics.split_for_impl();
 
+    let make = impl_make(&config, data, name, root)?;
+
+    let required = required_fields(&data.fields, root)?;
+    let default_impl = if required.is_empty() {
+        let default_trait = root.default_trait();
+        quote! {
+            #[automatically_derived]
+            impl #impl_g #default_trait for #name #type_g #where_clause {
+                fn default() -> Self {
+                    #block
+                }
+            }
+        }
+    } else {
+        // `new` is shared with `ConstDefault`, so it is a `const fn` when both are derived
+        impl_new(&required, &block, config.constant, vis, name, generics)
+    };
+
+    let with = match &config.with {
+        Some(with) => impl_with_setters(with, vis, data, name, generics)?,
+        None => TokenStream::new(),
+    };
+
+    let explicit_fields = impl_explicit_fields(vis, data, name, generics);
+
     Ok(quote! {
-        #[automatically_derived]
-        impl #impl_g ::fancy_default::traits::Default for #name #type_g #where_clause {
-            fn default() -> Self {
+        #default_impl
+
+        #make
+
+        #with
+
+        #explicit_fields
+    })
+}
+
+/// The fields marked `required`, in declaration order,
+/// named as the bindings used by the generated blocks, with their `#[cfg]` attributes.
+fn required_fields<'a>(
+    fields: &'a Fields,
+    root: &CrateRoot,
+) -> syn::Result<Vec<(Ident, &'a Type, Vec<Attribute>)>> {
+    let mut required = Vec::new();
+    for (idx, field) in fields.iter().enumerate() {
+        let config = FieldConfig::parse_attr(&field.attrs, root)?;
+        if config.required {
+            let ident = field
+                .ident
+                .clone()
+                .unwrap_or_else(|| format_ident!("field_{}", idx));
+            required.push((ident, &field.ty, config.cfg_attrs));
+        }
+    }
+    Ok(required)
+}
+
+/// Generate the `new` constructor taking the `required` fields,
+/// as a `const fn` if `constant` is set.
+fn impl_new(
+    required: &[(Ident, &Type, Vec<Attribute>)],
+    block: &TokenStream,
+    constant: bool,
+    vis: &Visibility,
+    name: &Ident,
+    generics: &Generics,
+) -> TokenStream {
+    let (impl_g, type_g, where_clause) = generics.split_for_impl();
+    let (ident, ty, cfg): (Vec<_>, Vec<_>, Vec<_>) = required.iter().cloned().multiunzip();
+    let constness = constant.then(|| quote! { const });
+    quote! {
+        impl #impl_g #name #type_g #where_clause {
+            #vis #constness fn new(#(#(#cfg)* #ident: #ty),*) -> Self {
                 #block
             }
         }
-    })
+    }
+}
+
+/// The `#[cfg]` attributes among `attrs`.
+pub(crate) fn cfg_attrs(attrs: &[Attribute]) -> Vec<Attribute> {
+    attrs
+        .iter()
+        .filter(|a| a.path().is_ident("cfg"))
+        .cloned()
+        .collect()
+}
+
+/// Make the `cfg` predicates mutually exclusive, where the first matching one wins.
+pub(crate) fn gated_predicates<'a>(
+    predicates: impl Iterator<Item = &'a TokenStream>,
+) -> Vec<TokenStream> {
+    let mut previous = Vec::new();
+    predicates
+        .map(|predicate| {
+            let gated = quote! { all(#predicate, not(any(#(#previous),*))) };
+            previous.push(predicate);
+            gated
+        })
+        .collect()
+}
+
+/// Whether the type is written as `Setting<T>`, matched by the last path segment.
+pub(crate) fn is_setting(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) if path.qself.is_none() => path.path.segments.last().is_some_and(|seg| {
+            seg.ident == "Setting" && matches!(seg.arguments, PathArguments::AngleBracketed(_))
+        }),
+        Type::Group(group) => is_setting(&group.elem),
+        Type::Paren(paren) => is_setting(&paren.elem),
+        _ => false,
+    }
+}
+
+/// Generate `explicit_fields`, listing the `Setting` fields explicitly set,
+/// if there are any `Setting` fields.
+fn impl_explicit_fields(
+    vis: &Visibility,
+    data: &DataStruct,
+    name: &Ident,
+    generics: &Generics,
+) -> TokenStream {
+    let (member, key, cfg): (Vec<_>, Vec<_>, Vec<_>) = data
+        .fields
+        .iter()
+        .enumerate()
+        .filter(|(_, field)| is_setting(&field.ty))
+        .map(|(idx, field)| {
+            let cfg = cfg_attrs(&field.attrs);
+            match &field.ident {
+                Some(ident) => {
+                    let key = ident.to_string();
+                    let key = key.strip_prefix("r#").unwrap_or(&key).to_owned();
+                    (Member::Named(ident.clone()), key, cfg)
+                }
+                None => (Member::Unnamed(Index::from(idx)), idx.to_string(), cfg),
+            }
+        })
+        .multiunzip();
+    if member.is_empty() {
+        return TokenStream::new();
+    }
+
+    let (impl_g, type_g, where_clause) = generics.split_for_impl();
+    quote! {
+        impl #impl_g #name #type_g #where_clause {
+            /// The names of the `Setting` fields explicitly set, in declaration order.
+            #vis fn explicit_fields(&self) -> impl ::core::iter::Iterator<Item = &'static str> {
+                [#(#(#cfg)* (#key, self.#member.is_explicit())),*]
+                    .into_iter()
+                    .filter_map(|(key, explicit)| explicit.then_some(key))
+            }
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+#[non_exhaustive]
+pub(crate) struct StructConfig {
+    /// The name of the generated struct-literal macro.
+    pub(crate) make: Option<Ident>,
+    /// Generate chainable `with_<field>` setters.
+    pub(crate) with: Option<WithConfig>,
+    /// The context type of `DefaultWith`.
+    pub(crate) ctx: Option<Type>,
+    /// Extra derives of the partial struct generated by `DefaultPartial`.
+    pub(crate) partial_derive: Vec<Path>,
+    /// Reject unknown fields when deserializing.
+    pub(crate) deny_unknown_fields: bool,
+    /// The case convention of the serialized field names.
+    pub(crate) rename_all: Option<RenameRule>,
+    /// Omit the fields equal to their default values when serializing, `true` if not given.
+    pub(crate) skip_defaults: Option<bool>,
+    /// The path of the `fancy_default` crate used by the generated code.
+    pub(crate) crate_path: Option<CratePath>,
+    /// The struct derives both `Default` and `ConstDefault`,
+    /// so the inherent items they share are only generated by `Default`.
+    pub(crate) constant: bool,
+}
+
+/// The `rename_all` case conventions, named as in serde.
+#[derive(Clone, Copy)]
+pub(crate) enum RenameRule {
+    Lower,
+    Upper,
+    Pascal,
+    Camel,
+    Snake,
+    ScreamingSnake,
+    Kebab,
+    ScreamingKebab,
+}
+
+impl RenameRule {
+    fn parse(lit: &LitStr) -> syn::Result<RenameRule> {
+        Ok(match lit.value().as_str() {
+            "lowercase" => RenameRule::Lower,
+            "UPPERCASE" => RenameRule::Upper,
+            "PascalCase" => RenameRule::Pascal,
+            "camelCase" => RenameRule::Camel,
+            "snake_case" => RenameRule::Snake,
+            "SCREAMING_SNAKE_CASE" => RenameRule::ScreamingSnake,
+            "kebab-case" => RenameRule::Kebab,
+            "SCREAMING-KEBAB-CASE" => RenameRule::ScreamingKebab,
+            _ => return Err(syn::Error::new(lit.span(), "unknown `rename_all` rule")),
+        })
+    }
+
+    /// Rename a snake case field name.
+    pub(crate) fn apply(self, field: &str) -> String {
+        match self {
+            RenameRule::Lower | RenameRule::Snake => field.to_owned(),
+            RenameRule::Upper | RenameRule::ScreamingSnake => field.to_ascii_uppercase(),
+            RenameRule::Pascal => field.to_camel(),
+            RenameRule::Camel => field.to_camel_lowercase(),
+            RenameRule::Kebab => field.replace('_', "-"),
+            RenameRule::ScreamingKebab => field.replace('_', "-").to_ascii_uppercase(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct WithConfig {
+    /// Defaults to the visibility of the struct.
+    vis: Option<Visibility>,
+    into: Option<bool>,
+    constant: bool,
+}
+
+impl StructConfig {
+    pub(crate) fn parse_attr(attrs: &[Attribute]) -> syn::Result<StructConfig> {
+        let mut config = StructConfig::default();
+
+        for attr in attrs.iter().filter(|a| a.meta.path().is_ident("default")) {
+            match &attr.meta {
+                Meta::List(meta_list) => meta_list.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("crate") {
+                        config.crate_path = Some(CratePath::parse(meta.value()?)?);
+                        return Ok(());
+                    }
+
+                    if meta.path.is_ident("core") {
+                        config.crate_path = Some(CratePath::Core);
+                        return Ok(());
+                    }
+
+                    if meta.path.is_ident("const") {
+                        config.constant = true;
+                        return Ok(());
+                    }
+
+                    if meta.path.is_ident("make") {
+                        config.make = Some(meta.value()?.parse()?);
+                        return Ok(());
+                    }
+
+                    if meta.path.is_ident("ctx") {
+                        config.ctx = Some(meta.value()?.parse()?);
+                        return Ok(());
+                    }
+
+                    if meta.path.is_ident("deny_unknown_fields") {
+                        config.deny_unknown_fields = true;
+                        return Ok(());
+                    }
+
+                    if meta.path.is_ident("skip_defaults") {
+                        config.skip_defaults = Some(if meta.input.peek(Token![=]) {
+                            meta.value()?.parse::<LitBool>()?.value()
+                        } else {
+                            true
+                        });
+                        return Ok(());
+                    }
+
+                    if meta.path.is_ident("rename_all") {
+                        config.rename_all = Some(RenameRule::parse(&meta.value()?.parse()?)?);
+                        return Ok(());
+                    }
+
+                    if meta.path.is_ident("partial") {
+                        return meta.parse_nested_meta(|meta| {
+                            if meta.path.is_ident("derive") {
+                                return meta.parse_nested_meta(|meta| {
+                                    config.partial_derive.push(meta.path);
+                                    Ok(())
+                                });
+                            }
+
+                            Err(meta.error("unrecognized `partial` parameter"))
+                        });
+                    }
+
+                    if meta.path.is_ident("with") {
+                        let with = config.with.insert(WithConfig {
+                            vis: None,
+                            into: None,
+                            constant: false,
+                        });
+                        if meta.input.peek(token::Paren) {
+                            meta.parse_nested_meta(|meta| {
+                                if meta.path.is_ident("vis") {
+                                    let vis: LitStr = meta.value()?.parse()?;
+                                    with.vis = Some(vis.parse()?);
+                                    return Ok(());
+                                }
+
+                                if meta.path.is_ident("into") {
+                                    with.into = Some(if meta.input.peek(Token![=]) {
+                                        meta.value()?.parse::<LitBool>()?.value()
+                                    } else {
+                                        true
+                                    });
+                                    return Ok(());
+                                }
+
+                                if meta.path.is_ident("const") || meta.path.is_ident("constant") {
+                                    with.constant = if meta.input.peek(Token![=]) {
+                                        meta.value()?.parse::<LitBool>()?.value()
+                                    } else {
+                                        true
+                                    };
+                                    return Ok(());
+                                }
+
+                                Err(meta.error("unrecognized `with` parameter"))
+                            })?;
+                        }
+
+                        if with.constant && with.into == Some(true) {
+                            return Err(meta.error("`into` is not supported by `const` setters"));
+                        }
+                        return Ok(());
+                    }
+
+                    Err(meta.error("unrecognized struct default parameter"))
+                })?,
+                _ => Err(syn::Error::new(
+                    attr.meta.span(),
+                    "expected `#[default(<config>)]`",
+                ))?,
+            }
+        }
+
+        Ok(config)
+    }
 }
 
 #[derive(Clone)]
 #[non_exhaustive]
-struct FieldConfig {
-    expr: Expr,
+pub(crate) struct FieldConfig {
+    pub(crate) expr: Expr,
+    /// Whether `expr` is given explicitly, rather than falling back to `Default::default()`.
+    pub(crate) explicit: bool,
 
     /// reserved for future usage
     #[allow(dead_code)]
-    constant: bool,
+    pub(crate) constant: bool,
+
+    /// `Some` if the field is marked as `nested`, holding the sub-field overrides.
+    pub(crate) nested: Option<Vec<NestedOverride>>,
+
+    /// The field has no default value, and must be given to the constructor.
+    pub(crate) required: bool,
+
+    /// The fallible default value used by `TryDefault`, with its error type.
+    pub(crate) try_expr: Option<(Expr, Option<Type>)>,
+
+    /// The context-dependent default value used by `DefaultWith`.
+    pub(crate) ctx: Option<Expr>,
+
+    /// The asynchronous default value used by `AsyncDefault`, containing `.await`.
+    pub(crate) async_expr: Option<Expr>,
+
+    /// The `DefaultProvider` that `expr` calls, if any.
+    pub(crate) provider: Option<Type>,
+
+    /// The serialized name of the field.
+    pub(crate) rename: Option<LitStr>,
+
+    /// The default values of older versions, used by `VersionedDefault`.
+    pub(crate) versions: Option<Versions>,
+
+    /// The `cfg` predicates with their default values, already folded into `expr`.
+    pub(crate) cfg: Vec<(TokenStream, Expr)>,
+
+    /// The explicit default value used when none of the `cfg` predicates holds.
+    pub(crate) cfg_fallback: Option<Expr>,
+
+    /// The `#[cfg]` attributes of the field, copied onto the code generated for it.
+    ///
+    /// Only `#[defaults]` and `structure!` see them, derive inputs are already configured.
+    pub(crate) cfg_attrs: Vec<Attribute>,
+
+    /// Where the code generated for the field finds the items of `fancy_default`.
+    pub(crate) crate_root: CrateRoot,
+}
+
+/// The default values of a field across versions, where `expr` is the newest one.
+#[derive(Clone)]
+pub(crate) struct Versions {
+    /// The value of the versions older than all the `entries`, the first entry's value if not given.
+    pub(crate) before: Option<Expr>,
+    /// The versions with the values they introduced, in ascending order.
+    pub(crate) entries: Vec<(VersionLit, Expr)>,
+}
+
+/// A `major.minor.patch` version, checked at compile time.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct VersionLit(u64, u64, u64);
+
+impl VersionLit {
+    /// Parse `major`, `major.minor` or `major.minor.patch`, like `fancy_default::version::Version`.
+    fn parse(lit: &LitStr) -> syn::Result<VersionLit> {
+        let value = lit.value();
+        let mut components = [0; 3];
+        let mut parts = value.split('.');
+        for component in components.iter_mut() {
+            match parts.next() {
+                Some(part) if !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit()) => {
+                    *component = part.parse().map_err(|_| invalid_version(lit))?;
+                }
+                Some(_) => return Err(invalid_version(lit)),
+                None => break,
+            }
+        }
+        if parts.next().is_some() {
+            return Err(invalid_version(lit));
+        }
+        let [major, minor, patch] = components;
+        Ok(VersionLit(major, minor, patch))
+    }
+}
+
+fn invalid_version(lit: &LitStr) -> syn::Error {
+    syn::Error::new(
+        lit.span(),
+        format!(
+            "invalid version `{}`, expected `major[.minor[.patch]]`",
+            lit.value()
+        ),
+    )
+}
+
+impl VersionLit {
+    /// The `Version` constant, with `fancy_default` as the path to the crate.
+    fn to_expr(self, fancy_default: &TokenStream) -> TokenStream {
+        let VersionLit(major, minor, patch) = self;
+        quote! {
+            #fancy_default::version::Version::new(#major, #minor, #patch)
+        }
+    }
+}
+
+/// A single `"<version>" = <expr>` entry of `#[default(versions(...))]`.
+struct VersionEntry {
+    version: LitStr,
+    value: Expr,
+}
+
+impl Parse for VersionEntry {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let version = input.parse()?;
+        input.parse::<Token![=]>()?;
+        let value = input.parse()?;
+        Ok(VersionEntry { version, value })
+    }
+}
+
+/// A single `path.to.field = <expr>` entry of `#[default(nested(...))]`.
+#[derive(Clone)]
+pub(crate) struct NestedOverride {
+    pub(crate) path: Punctuated<Member, Token![.]>,
+    pub(crate) value: Expr,
+}
+
+impl Parse for NestedOverride {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let path = Punctuated::parse_separated_nonempty(input)?;
+        input.parse::<Token![=]>()?;
+        let value = input.parse()?;
+        Ok(NestedOverride { path, value })
+    }
 }
 
 impl FieldConfig {
-    fn parse_attr(attrs: &[Attribute]) -> syn::Result<FieldConfig> {
+    pub(crate) fn parse_attr(attrs: &[Attribute], root: &CrateRoot) -> syn::Result<FieldConfig> {
         let mut constant = false;
-        let mut expr: Expr = parse_quote! {::fancy_default::traits::Default::default()};
+        let default_trait = root.default_trait();
+        let mut expr: Expr = parse_quote! {#default_trait::default()};
+        let mut nested: Option<Vec<NestedOverride>> = None;
+        let mut explicit = false;
+        let mut required = None;
+        let mut try_expr: Option<Expr> = None;
+        let mut error: Option<Type> = None;
+        let mut ctx: Option<Expr> = None;
+        let mut async_expr: Option<Expr> = None;
+        let mut provider: Option<Type> = None;
+        let mut rename: Option<LitStr> = None;
+        let mut since: Option<LitStr> = None;
+        let mut before: Option<Expr> = None;
+        let mut versions: Option<(Span, Vec<VersionEntry>)> = None;
+        let mut cfg: Vec<(TokenStream, Expr)> = Vec::new();
+        let mut cfg_span: Option<Span> = None;
 
         for attr in attrs.iter().filter(|a| a.meta.path().is_ident("default")) {
             match &attr.meta {
@@ -82,21 +617,370 @@ impl FieldConfig {
                     if meta.path.is_ident("expr") {
                         let val: Expr = meta.value()?.parse()?;
                         expr = val;
+                        explicit = true;
+                        return Ok(());
+                    }
+
+                    if meta.path.is_ident("provider") {
+                        provider = Some(meta.value()?.parse()?);
+                        return Ok(());
+                    }
+
+                    if meta.path.is_ident("rename") {
+                        rename = Some(meta.value()?.parse()?);
+                        return Ok(());
+                    }
+
+                    if meta.path.is_ident("cfg") {
+                        let content;
+                        parenthesized!(content in meta.input);
+                        let predicate: TokenStream = content.parse()?;
+                        cfg.push((predicate, meta.value()?.parse()?));
+                        cfg_span.get_or_insert(meta.path.span());
+                        return Ok(());
+                    }
+
+                    if meta.path.is_ident("since") {
+                        since = Some(meta.value()?.parse()?);
+                        return Ok(());
+                    }
+
+                    if meta.path.is_ident("before") {
+                        before = Some(meta.value()?.parse()?);
+                        return Ok(());
+                    }
+
+                    if meta.path.is_ident("versions") {
+                        let content;
+                        parenthesized!(content in meta.input);
+                        let entries =
+                            Punctuated::<VersionEntry, Token![,]>::parse_terminated(&content)?;
+                        if entries.is_empty() {
+                            return Err(meta.error("`versions` requires at least one version"));
+                        }
+                        versions = Some((meta.path.span(), entries.into_iter().collect()));
+                        return Ok(());
+                    }
+
+                    if meta.path.is_ident("try") {
+                        try_expr = Some(meta.value()?.parse()?);
+                        return Ok(());
+                    }
+
+                    if meta.path.is_ident("ctx") {
+                        ctx = Some(meta.value()?.parse()?);
+                        return Ok(());
+                    }
+
+                    if meta.path.is_ident("async") {
+                        async_expr = Some(meta.value()?.parse()?);
+                        return Ok(());
+                    }
+
+                    if meta.path.is_ident("error") {
+                        error = Some(meta.value()?.parse()?);
+                        return Ok(());
+                    }
+
+                    if meta.path.is_ident("required") {
+                        let value = if meta.input.peek(Token![=]) {
+                            let val: LitBool = meta.value()?.parse()?;
+                            val.value()
+                        } else {
+                            true
+                        };
+                        required = value.then(|| meta.path.span());
+                        return Ok(());
+                    }
+
+                    if meta.path.is_ident("nested") {
+                        let overrides = nested.get_or_insert_with(Vec::new);
+                        if meta.input.peek(token::Paren) {
+                            let content;
+                            parenthesized!(content in meta.input);
+                            for item in
+                                Punctuated::<NestedOverride, Token![,]>::parse_terminated(&content)?
+                            {
+                                let path = item.path.to_token_stream().to_string();
+                                if overrides
+                                    .iter()
+                                    .any(|o| o.path.to_token_stream().to_string() == path)
+                                {
+                                    return Err(syn::Error::new_spanned(
+                                        &item.path,
+                                        "duplicate nested override",
+                                    ));
+                                }
+                                overrides.push(item);
+                            }
+                        }
                         return Ok(());
                     }
 
                     Err(meta.error("unrecognized default parameter"))
                 })?,
-                Meta::NameValue(nv) => expr = nv.value.clone(),
+                Meta::NameValue(nv) => {
+                    expr = nv.value.clone();
+                    explicit = true;
+                }
                 _ => {}
             }
         }
 
-        Ok(FieldConfig { constant, expr })
+        if let Some(provider) = &provider {
+            if explicit {
+                return Err(syn::Error::new(
+                    provider.span(),
+                    "`provider` cannot be combined with an explicit default value",
+                ));
+            }
+            let fancy_default = root.path()?;
+            expr = parse_quote! {
+                <#provider as #fancy_default::traits::DefaultProvider<_>>::provide()
+            };
+            explicit = true;
+        }
+
+        let mut cfg_fallback = None;
+        if let Some(span) = cfg_span {
+            // the explicit default value, if any, is used when no predicate matches
+            if explicit {
+                cfg_fallback = Some(expr.clone());
+            }
+            let fallback = if explicit {
+                expr.to_token_stream()
+            } else {
+                quote_spanned! {span=>
+                    ::core::compile_error!("no `cfg` default value matches the current configuration")
+                }
+            };
+            let predicate = cfg.iter().map(|(predicate, _)| predicate);
+            let gated = gated_predicates(predicate.clone());
+            let value = cfg.iter().map(|(_, value)| value);
+            expr = parse_quote! {{
+                #(
+                    #[cfg(#gated)]
+                    let __value = #value;
+                )*
+                #[cfg(not(any(#(#predicate),*)))]
+                let __value = #fallback;
+                __value
+            }};
+            explicit = true;
+        }
+
+        let versions = match (since, versions) {
+            (Some(since), Some(_)) => {
+                return Err(syn::Error::new(
+                    since.span(),
+                    "`since` cannot be combined with `versions`",
+                ))
+            }
+            (Some(since), None) => {
+                let Some(before) = before.take() else {
+                    return Err(syn::Error::new(
+                        since.span(),
+                        "`since` requires the value of the older versions, given by `before`",
+                    ));
+                };
+                Some(Versions {
+                    before: Some(before),
+                    entries: vec![(VersionLit::parse(&since)?, expr.clone())],
+                })
+            }
+            (None, Some((span, entries))) => {
+                if explicit {
+                    return Err(syn::Error::new(
+                        span,
+                        "`versions` cannot be combined with an explicit default value",
+                    ));
+                }
+                let mut entries = entries
+                    .into_iter()
+                    .map(|entry| Ok((VersionLit::parse(&entry.version)?, entry.value)))
+                    .collect::<syn::Result<Vec<_>>>()?;
+                entries.sort_by_key(|(version, _)| *version);
+                if let Some((_, (_, value))) = entries
+                    .iter()
+                    .tuple_windows()
+                    .find(|((a, _), (b, _))| a == b)
+                {
+                    return Err(syn::Error::new_spanned(value, "duplicate version"));
+                }
+                expr = entries[entries.len() - 1].1.clone();
+                explicit = true;
+                Some(Versions {
+                    before: before.take(),
+                    entries,
+                })
+            }
+            (None, None) => None,
+        };
+        if let Some(before) = &before {
+            return Err(syn::Error::new(
+                before.span(),
+                "`before` is only supported along with `since` or `versions`",
+            ));
+        }
+
+        if let Some(span) = required {
+            if explicit
+                || nested.is_some()
+                || ctx.is_some()
+                || async_expr.is_some()
+                || versions.is_some()
+            {
+                return Err(syn::Error::new(
+                    span,
+                    "a `required` field cannot have a default value",
+                ));
+            }
+        }
+
+        if let (None, Some(error)) = (&try_expr, &error) {
+            return Err(syn::Error::new(
+                error.span(),
+                "`error` is only supported along with `try`",
+            ));
+        }
+
+        Ok(FieldConfig {
+            constant,
+            expr,
+            explicit,
+            nested,
+            required: required.is_some(),
+            try_expr: try_expr.map(|expr| (expr, error)),
+            ctx,
+            async_expr,
+            provider,
+            rename,
+            versions,
+            cfg,
+            cfg_fallback,
+            cfg_attrs: cfg_attrs(attrs),
+            crate_root: root.clone(),
+        })
+    }
+
+    /// A human readable form of the default value, in the same syntax as the attribute.
+    pub(crate) fn expr_string(&self) -> String {
+        let expr = if !self.cfg.is_empty() {
+            let cfg = self
+                .cfg
+                .iter()
+                .map(|(predicate, value)| {
+                    format!("cfg({}) = {}", predicate, value.to_token_stream())
+                })
+                .join(", ");
+            if let Some(provider) = &self.provider {
+                format!("{}, provider = {}", cfg, provider.to_token_stream())
+            } else if let Some(fallback) = &self.cfg_fallback {
+                format!("{}, expr = {}", cfg, fallback.to_token_stream())
+            } else {
+                cfg
+            }
+        } else if let Some(provider) = &self.provider {
+            format!("<{} as DefaultProvider<_>>::provide()", provider.to_token_stream())
+        } else if self.explicit {
+            self.expr.to_token_stream().to_string()
+        } else {
+            "Default::default()".to_owned()
+        };
+        match &self.nested {
+            Some(overrides) if !overrides.is_empty() => {
+                let overrides = overrides
+                    .iter()
+                    .map(|o| format!("{} = {}", o.path.to_token_stream(), o.value.to_token_stream()))
+                    .join(", ");
+                format!("{}, nested({})", expr, overrides)
+            }
+            _ => expr,
+        }
+    }
+
+    /// The expression used to initialize a field of type `ty`,
+    /// with the nested overrides applied on top of `expr`.
+    ///
+    /// Explicit values of `Setting` fields are wrapped in `Setting::Default`.
+    pub(crate) fn default_value(&self, ty: &Type) -> TokenStream {
+        if self.explicit {
+            self.default_value_from(ty, self.explicit_value(ty, &self.expr))
+        } else {
+            self.default_value_from(ty, self.expr.to_token_stream())
+        }
+    }
+
+    /// The expression used to initialize a field of type `ty` as of `version`,
+    /// an expression of type `&fancy_default::version::Version`.
+    pub(crate) fn versioned_value(&self, ty: &Type, version: &TokenStream) -> TokenStream {
+        let fancy_default = match self.crate_root.path() {
+            Ok(fancy_default) => fancy_default,
+            Err(e) => return e.to_compile_error(),
+        };
+        let Some(versions) = &self.versions else {
+            if self.nested.is_some() && !self.explicit {
+                return self.default_value_from(
+                    ty,
+                    quote! {
+                        <#ty as #fancy_default::traits::VersionedDefault>::default_for_version(#version)
+                    },
+                );
+            }
+            return self.default_value(ty);
+        };
+
+        let fallback = versions.before.as_ref().unwrap_or(&versions.entries[0].1);
+        let mut value = self.explicit_value(ty, fallback);
+        for (since, expr) in &versions.entries {
+            let since = since.to_expr(&fancy_default);
+            let expr = self.explicit_value(ty, expr);
+            value = quote! {
+                if #version >= &#since {
+                    #expr
+                } else {
+                    #value
+                }
+            };
+        }
+        self.default_value_from(ty, value)
+    }
+
+    /// An explicit default value of a field of type `ty`, wrapped in `Setting::Default` if needed.
+    fn explicit_value(&self, ty: &Type, expr: &Expr) -> TokenStream {
+        if !is_setting(ty) {
+            return expr.to_token_stream();
+        }
+        match self.crate_root.path() {
+            Ok(fancy_default) => quote! { #fancy_default::wrapper::Setting::Default(#expr) },
+            Err(e) => e.to_compile_error(),
+        }
+    }
+
+    /// Same as [`default_value`](Self::default_value), but starting from `expr` instead.
+    pub(crate) fn default_value_from(&self, ty: &Type, expr: TokenStream) -> TokenStream {
+        match &self.nested {
+            Some(overrides) if !overrides.is_empty() => {
+                let (path, value): (Vec<_>, Vec<_>) =
+                    overrides.iter().map(|o| (&o.path, &o.value)).unzip();
+                quote! {{
+                    let mut __nested: #ty = #expr;
+                    #(
+                        __nested.#path = #value;
+                    )*
+                    __nested
+                }}
+            }
+            _ => expr,
+        }
     }
 }
 
-fn impl_named_struct(fields: &FieldsNamed, name: &Ident) -> syn::Result<TokenStream> {
+fn impl_named_struct(
+    fields: &FieldsNamed,
+    name: &Ident,
+    root: &CrateRoot,
+) -> syn::Result<TokenStream> {
     let result = fields
         .named
         .iter()
@@ -107,7 +991,7 @@ fn impl_named_struct(fields: &FieldsNamed, name: &Ident) -> syn::Result<TokenStr
                     .as_ref()
                     .unwrap_or_else(|| panic!("unexpected internal error: unnamed field")),
                 &field.ty,
-                FieldConfig::parse_attr(&field.attrs).map_err(|e| e.to_compile_error())?,
+                FieldConfig::parse_attr(&field.attrs, root).map_err(|e| e.to_compile_error())?,
             ))
         })
         .collect::<Result<Vec<_>, _>>();
@@ -117,23 +1001,31 @@ fn impl_named_struct(fields: &FieldsNamed, name: &Ident) -> syn::Result<TokenStr
         Err(e) => return Ok(e),
     };
 
-    let (ident, ty, expr): (Vec<&Ident>, Vec<&Type>, Vec<Expr>) = result
-        .into_iter()
-        .map(|(a, b, c)| (a, b, c.expr))
+    let (all_ident, all_cfg): (Vec<_>, Vec<_>) =
+        result.iter().map(|(a, _, c)| (*a, &c.cfg_attrs)).unzip();
+    let (ident, ty, expr, cfg): (Vec<&Ident>, Vec<&Type>, Vec<TokenStream>, Vec<_>) = result
+        .iter()
+        .filter(|(_, _, c)| !c.required)
+        .map(|(a, b, c)| (*a, *b, c.default_value(b), &c.cfg_attrs))
         .multiunzip();
 
     Ok(quote! {{
         #(
+            #(#cfg)*
             let #ident: #ty = #expr;
         )*
 
         #name {
-            #(#ident,)*
+            #(#(#all_cfg)* #all_ident,)*
         }
     }})
 }
 
-fn impl_unnamed_struct(fields: &FieldsUnnamed, name: &Ident) -> syn::Result<TokenStream> {
+fn impl_unnamed_struct(
+    fields: &FieldsUnnamed,
+    name: &Ident,
+    root: &CrateRoot,
+) -> syn::Result<TokenStream> {
     let result = fields
         .unnamed
         .iter()
@@ -142,7 +1034,7 @@ fn impl_unnamed_struct(fields: &FieldsUnnamed, name: &Ident) -> syn::Result<Toke
             Ok((
                 format_ident!("field_{}", idx),
                 &field.ty,
-                FieldConfig::parse_attr(&field.attrs).map_err(|e| e.to_compile_error())?,
+                FieldConfig::parse_attr(&field.attrs, root).map_err(|e| e.to_compile_error())?,
             ))
         })
         .collect::<Result<Vec<_>, _>>();
@@ -152,16 +1044,171 @@ fn impl_unnamed_struct(fields: &FieldsUnnamed, name: &Ident) -> syn::Result<Toke
         Err(e) => return Ok(e),
     };
 
-    let (ident, ty, expr): (Vec<Ident>, Vec<&Type>, Vec<Expr>) = result
-        .into_iter()
-        .map(|(a, b, c)| (a, b, c.expr))
+    let (all_ident, all_cfg): (Vec<_>, Vec<_>) =
+        result.iter().map(|(a, _, c)| (a, &c.cfg_attrs)).unzip();
+    let (ident, ty, expr, cfg): (Vec<&Ident>, Vec<&Type>, Vec<TokenStream>, Vec<_>) = result
+        .iter()
+        .filter(|(_, _, c)| !c.required)
+        .map(|(a, b, c)| (a, *b, c.default_value(b), &c.cfg_attrs))
         .multiunzip();
 
     Ok(quote! {{
         #(
+            #(#cfg)*
             let #ident: #ty = #expr;
         )*
 
-        #name(#(#ident),*)
+        #name(#(#(#all_cfg)* #all_ident),*)
     }})
 }
+
+/// Generate the macro given by `#[default(make = ...)]`, if any.
+fn impl_make(
+    config: &StructConfig,
+    data: &DataStruct,
+    name: &Ident,
+    root: &CrateRoot,
+) -> syn::Result<TokenStream> {
+    match (&config.make, &data.fields) {
+        (Some(macro_name), Fields::Named(fields)) => {
+            impl_make_macro(fields, name, macro_name, root)
+        }
+        (Some(macro_name), _) => Err(syn::Error::new(
+            macro_name.span(),
+            "`make` is only supported on structs with named fields",
+        )),
+        (None, _) => Ok(TokenStream::new()),
+    }
+}
+
+/// Generate a `macro_rules!` macro building the struct from a partial struct literal,
+/// where only the default values of the missing fields are evaluated.
+fn impl_make_macro(
+    fields: &FieldsNamed,
+    name: &Ident,
+    macro_name: &Ident,
+    root: &CrateRoot,
+) -> syn::Result<TokenStream> {
+    let fields = fields
+        .named
+        .iter()
+        .map(|field| {
+            Ok((
+                field
+                    .ident
+                    .as_ref()
+                    .unwrap_or_else(|| panic!("unexpected internal error: unnamed field")),
+                &field.ty,
+                FieldConfig::parse_attr(&field.attrs, root)?,
+            ))
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    let selectors = fields.iter().map(|(ident, ty, config)| {
+        let expr = if config.required {
+            let message = format!("missing required field `{}`", ident);
+            quote! { ::core::compile_error!(#message) }
+        } else {
+            config.default_value(ty)
+        };
+        quote! {
+            (@#ident #ident: $value:expr, $($rest:tt)*) => { $value };
+            (@#ident $other:ident: $value:expr, $($rest:tt)*) => {
+                #macro_name!(@#ident $($rest)*)
+            };
+            (@#ident) => { #expr };
+        }
+    });
+    let (ident, cfg): (Vec<_>, Vec<_>) = fields.iter().map(|(i, _, c)| (*i, &c.cfg_attrs)).unzip();
+
+    Ok(quote! {
+        macro_rules! #macro_name {
+            #(#selectors)*
+            ($($field:ident: $value:expr),* $(,)?) => {{
+                // the generic parameters of the struct are not in scope at the call site,
+                // so the field types are inferred from the struct literal
+                #(
+                    #(#cfg)*
+                    let #ident = #macro_name!(@#ident $($field: $value,)*);
+                )*
+
+                let __value = #name {
+                    #(#(#cfg)* #ident,)*
+                };
+                // reject unknown and duplicate fields
+                if false {
+                    let #name { $($field: _,)* .. } = &__value;
+                }
+                __value
+            }};
+        }
+
+        #[allow(unused_imports)]
+        pub(crate) use #macro_name;
+    })
+}
+
+/// Generate the `with_<field>` setters,
+/// as `const fn`s taking the field type itself if `constant` is set.
+fn impl_with_setters(
+    config: &WithConfig,
+    vis: &Visibility,
+    data: &DataStruct,
+    name: &Ident,
+    generics: &Generics,
+) -> syn::Result<TokenStream> {
+    let Fields::Named(fields) = &data.fields else {
+        return Err(syn::Error::new(
+            name.span(),
+            "`with` is only supported on structs with named fields",
+        ));
+    };
+
+    let vis = config.vis.as_ref().unwrap_or(vis);
+    let setters = fields.named.iter().map(|field| {
+        let ident = field
+            .ident
+            .as_ref()
+            .unwrap_or_else(|| panic!("unexpected internal error: unnamed field"));
+        let ty = &field.ty;
+        let fn_name = format_ident!("with_{}", ident);
+        let cfg = cfg_attrs(&field.attrs);
+
+        if config.constant {
+            quote! {
+                #(#cfg)*
+                #[allow(clippy::needless_update)]
+                #vis const fn #fn_name(self, value: #ty) -> Self {
+                    Self {
+                        #ident: value,
+                        ..self
+                    }
+                }
+            }
+        } else if config.into.unwrap_or(true) {
+            quote! {
+                #(#cfg)*
+                #vis fn #fn_name(mut self, value: impl ::core::convert::Into<#ty>) -> Self {
+                    self.#ident = value.into();
+                    self
+                }
+            }
+        } else {
+            quote! {
+                #(#cfg)*
+                #vis fn #fn_name(mut self, value: #ty) -> Self {
+                    self.#ident = value;
+                    self
+                }
+            }
+        }
+    });
+
+    let (impl_g, type_g, where_clause) = generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_g #name #type_g #where_clause {
+            #(#setters)*
+        }
+    })
+}
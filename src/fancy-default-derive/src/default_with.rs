@@ -0,0 +1,179 @@
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::{parse2, DeriveInput, Expr, Fields, Pat, Type};
+
+use crate::crate_path::CrateRoot;
+use crate::default::{
+    enum_impl::{self, EnumConfig},
+    struct_impl::{FieldConfig, StructConfig},
+};
+
+pub(crate) fn process_default_with(input: TokenStream) -> syn::Result<TokenStream> {
+    let input: DeriveInput = parse2(input)?;
+    enum_impl::expand_cfg_variants(&input, impl_default_with)
+}
+
+pub(crate) fn impl_default_with(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+    let root = CrateRoot::of(input)?;
+    let fancy_default = root.path()?;
+
+    let (ctx, body) = match &input.data {
+        syn::Data::Struct(struct_data) => {
+            let ctx = StructConfig::parse_attr(&input.attrs)?.ctx;
+            let fields = parse_fields(&struct_data.fields, &root)?;
+            let ctx = context_type(ctx, &fields)?;
+            let body = impl_fields(
+                &struct_data.fields,
+                &fields,
+                &ctx,
+                quote! { #name },
+                &fancy_default,
+            );
+            (ctx, body)
+        }
+        syn::Data::Enum(enum_data) => {
+            let ctx = EnumConfig::parse_attr(&input.attrs)?.ctx;
+            let default = enum_impl::select_default_variant(&input.attrs, enum_data)?;
+            match &default.expr {
+                Some(expr) => (context_type(ctx, &[])?, quote! { #expr }),
+                None => {
+                    let variant_name = &default.variant.ident;
+                    let fields = parse_fields(&default.variant.fields, &root)?;
+                    let ctx = context_type(ctx, &fields)?;
+                    let body = impl_fields(
+                        &default.variant.fields,
+                        &fields,
+                        &ctx,
+                        quote! { #name::#variant_name },
+                        &fancy_default,
+                    );
+                    (ctx, body)
+                }
+            }
+        }
+        syn::Data::Union(_) => {
+            return Err(syn::Error::new(
+                Span::call_site(),
+                "`DefaultWith` is not supported on unions",
+            ))
+        }
+    };
+
+    let (impl_g, type_g, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_g #fancy_default::traits::DefaultWith<#ctx> for #name #type_g #where_clause {
+            fn default_with(ctx: &#ctx) -> Self {
+                #[allow(unused_imports)]
+                use #fancy_default::__private::{ViaDefault as _, ViaDefaultWith as _};
+                let _ = ctx;
+                #body
+            }
+        }
+    })
+}
+
+fn parse_fields(fields: &Fields, root: &CrateRoot) -> syn::Result<Vec<FieldConfig>> {
+    fields
+        .iter()
+        .map(|field| {
+            let config = FieldConfig::parse_attr(&field.attrs, root)?;
+            if config.required {
+                return Err(syn::Error::new_spanned(
+                    field,
+                    "`required` fields are not supported by `DefaultWith`",
+                ));
+            }
+            Ok(config)
+        })
+        .collect()
+}
+
+/// The context type given by `#[default(ctx = <type>)]`,
+/// or the parameter type of the first `ctx` closure, e.g. `AppCtx` for `|c: &AppCtx| ...`.
+fn context_type(ctx: Option<Type>, fields: &[FieldConfig]) -> syn::Result<Type> {
+    if let Some(ctx) = ctx {
+        return Ok(ctx);
+    }
+
+    fields
+        .iter()
+        .filter_map(|config| closure_context(config.ctx.as_ref()?))
+        .next()
+        .ok_or_else(|| {
+            syn::Error::new(
+                Span::call_site(),
+                "cannot infer the context type, consider adding `#[default(ctx = <type>)]`",
+            )
+        })
+}
+
+/// Parse `AppCtx` out of a closure starting with `|c: &AppCtx|`.
+fn closure_context(expr: &Expr) -> Option<Type> {
+    let Expr::Closure(closure) = expr else {
+        return None;
+    };
+    match closure.inputs.first()? {
+        Pat::Type(pat) => match &*pat.ty {
+            Type::Reference(reference) => Some((*reference.elem).clone()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn impl_fields(
+    fields: &Fields,
+    configs: &[FieldConfig],
+    ctx: &Type,
+    path: TokenStream,
+    fancy_default: &TokenStream,
+) -> TokenStream {
+    let named = matches!(fields, Fields::Named(_));
+    let (idents, values): (Vec<_>, Vec<_>) = fields
+        .iter()
+        .zip(configs)
+        .enumerate()
+        .map(|(idx, (field, config))| {
+            let ident = field
+                .ident
+                .clone()
+                .unwrap_or_else(|| format_ident!("field_{}", idx));
+            let ty = &field.ty;
+            let value = match &config.ctx {
+                Some(expr) => config.default_value_from(
+                    ty,
+                    quote! { #fancy_default::__private::call_with::<#ctx, #ty>(#expr, ctx) },
+                ),
+                None if config.explicit => config.default_value(ty),
+                None => config.default_value_from(
+                    ty,
+                    quote! {
+                        (&#fancy_default::__private::DefaultWithProbe::<#ty, #ctx>::new())
+                            .default_with(ctx)
+                    },
+                ),
+            };
+            let cfg = &config.cfg_attrs;
+            (
+                quote! { #(#cfg)* #ident },
+                quote! { #(#cfg)* let #ident: #ty = #value; },
+            )
+        })
+        .unzip();
+
+    let construct = if named {
+        quote! { #path { #(#idents,)* } }
+    } else if idents.is_empty() {
+        path
+    } else {
+        quote! { #path(#(#idents),*) }
+    };
+
+    quote! {
+        #(#values)*
+        #construct
+    }
+}
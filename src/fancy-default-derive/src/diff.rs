@@ -0,0 +1,214 @@
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::{parse2, DataStruct, DeriveInput, Fields, Index, Member, Type};
+
+use crate::crate_path::CrateRoot;
+use crate::default::struct_impl::FieldConfig;
+
+pub(crate) fn process_diff_default(input: TokenStream) -> syn::Result<TokenStream> {
+    let input: DeriveInput = parse2(input)?;
+    impl_diff_default(&input)
+}
+
+pub(crate) fn process_debug_non_default(input: TokenStream) -> syn::Result<TokenStream> {
+    let input: DeriveInput = parse2(input)?;
+    impl_debug_non_default(&input)
+}
+
+pub(crate) fn impl_diff_default(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let fields = struct_fields(input, "DiffDefault")?;
+    let fancy_default = CrateRoot::of(input)?.path()?;
+
+    let diffs = fields.iter().map(|(member, ty, config)| {
+        let path = member_name(member);
+        let cfg = &config.cfg_attrs;
+        let diff = match &config.nested {
+            // the field type's own default values apply, unless the field declares its own
+            Some(overrides) if overrides.is_empty() && !config.explicit => quote! {
+                for diff in #fancy_default::traits::DiffDefault::non_default_fields(&self.#member) {
+                    diffs.push(#fancy_default::__private::nested_diff(#path, diff));
+                }
+            },
+            Some(_) => {
+                let default = config.default_value(ty);
+                quote! {
+                    let default: #ty = #default;
+                    for diff in #fancy_default::traits::DiffDefault::fields_differing_from(&self.#member, default) {
+                        diffs.push(#fancy_default::__private::nested_diff(#path, diff));
+                    }
+                }
+            }
+            None => {
+                let default = config.default_value(ty);
+                quote! {
+                    let default: #ty = #default;
+                    if self.#member != default {
+                        diffs.push(#fancy_default::__private::field_diff(#path, &self.#member, default));
+                    }
+                }
+            }
+        };
+        quote! { #(#cfg)* { #diff } }
+    });
+
+    let diffs_from = fields.iter().map(|(member, _, config)| {
+        let path = member_name(member);
+        let cfg = &config.cfg_attrs;
+        if config.nested.is_some() {
+            quote! {
+                #(#cfg)*
+                for diff in #fancy_default::traits::DiffDefault::fields_differing_from(&self.#member, default.#member) {
+                    diffs.push(#fancy_default::__private::nested_diff(#path, diff));
+                }
+            }
+        } else {
+            quote! {
+                #(#cfg)*
+                if self.#member != default.#member {
+                    diffs.push(#fancy_default::__private::field_diff(#path, &self.#member, default.#member));
+                }
+            }
+        }
+    });
+
+    let name = &input.ident;
+    let (impl_g, type_g, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_g #fancy_default::traits::DiffDefault for #name #type_g #where_clause {
+            fn non_default_fields(
+                &self,
+            ) -> impl ::core::iter::Iterator<Item = #fancy_default::traits::FieldDiff<'_>> {
+                #[allow(unused_mut)]
+                let mut diffs = #fancy_default::__private::Vec::new();
+                #(#diffs)*
+                diffs.into_iter()
+            }
+
+            fn fields_differing_from(
+                &self,
+                default: Self,
+            ) -> impl ::core::iter::Iterator<Item = #fancy_default::traits::FieldDiff<'_>> {
+                let _ = &default;
+                #[allow(unused_mut)]
+                let mut diffs = #fancy_default::__private::Vec::new();
+                #(#diffs_from)*
+                diffs.into_iter()
+            }
+        }
+    })
+}
+
+pub(crate) fn impl_debug_non_default(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let fields = struct_fields(input, "DebugNonDefault")?;
+    let tuple = matches!(
+        &input.data,
+        syn::Data::Struct(DataStruct {
+            fields: Fields::Unnamed(_),
+            ..
+        })
+    );
+
+    let entries = fields.iter().map(|(member, ty, config)| {
+        let default = config.default_value(ty);
+        let cfg = &config.cfg_attrs;
+        let entry = if tuple {
+            // the omitted fields are kept as `..`, so that the positions stay readable
+            quote! {
+                let default: #ty = #default;
+                if self.#member != default {
+                    debug.field(&self.#member);
+                } else {
+                    debug.field(&::core::format_args!(".."));
+                }
+            }
+        } else {
+            let field_name = member_name(member);
+            quote! {
+                let default: #ty = #default;
+                if self.#member != default {
+                    debug.field(#field_name, &self.#member);
+                } else {
+                    exhaustive = false;
+                }
+            }
+        };
+        quote! { #(#cfg)* { #entry } }
+    });
+
+    let name = &input.ident;
+    let name_str = name.to_string();
+    let (impl_g, type_g, where_clause) = input.generics.split_for_impl();
+
+    let body = if tuple {
+        quote! {
+            let mut debug = f.debug_tuple(#name_str);
+            #(#entries)*
+            debug.finish()
+        }
+    } else {
+        quote! {
+            let mut debug = f.debug_struct(#name_str);
+            #[allow(unused_mut)]
+            let mut exhaustive = true;
+            #(#entries)*
+            if exhaustive {
+                debug.finish()
+            } else {
+                debug.finish_non_exhaustive()
+            }
+        }
+    };
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_g ::core::fmt::Debug for #name #type_g #where_clause {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                #body
+            }
+        }
+    })
+}
+
+/// The fields of a struct with their configurations, rejecting enums and `required` fields.
+fn struct_fields<'a>(
+    input: &'a DeriveInput,
+    derive: &str,
+) -> syn::Result<Vec<(Member, &'a Type, FieldConfig)>> {
+    let syn::Data::Struct(DataStruct { fields, .. }) = &input.data else {
+        return Err(syn::Error::new(
+            Span::call_site(),
+            format!("`{}` is only supported on structs", derive),
+        ));
+    };
+    let root = CrateRoot::of(input)?;
+
+    fields
+        .iter()
+        .enumerate()
+        .map(|(idx, field)| {
+            let config = FieldConfig::parse_attr(&field.attrs, &root)?;
+            if config.required {
+                return Err(syn::Error::new_spanned(
+                    field,
+                    format!("`required` fields are not supported by `{}`", derive),
+                ));
+            }
+
+            let member = match &field.ident {
+                Some(ident) => Member::Named(ident.clone()),
+                None => Member::Unnamed(Index::from(idx)),
+            };
+            Ok((member, &field.ty, config))
+        })
+        .collect()
+}
+
+/// The field name, or its index for tuple fields.
+fn member_name(member: &Member) -> String {
+    match member {
+        Member::Named(ident) => ident.to_string(),
+        Member::Unnamed(index) => index.index.to_string(),
+    }
+}
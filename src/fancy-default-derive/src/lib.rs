@@ -1,16 +1,29 @@
 //! This crate is an internal implementation of the `fancy-default` library.
 //! 
-//! See the [`fancy-default`] library's documentation for instructions on how to use it.
+//! See the `fancy-default` library's documentation for instructions on how to use it.
 
 use proc_macro::TokenStream;
 
+mod async_default;
+mod attribute;
+mod builder;
+mod crate_path;
 mod default;
+mod default_with;
+mod diff;
+mod merge;
+mod overrides;
+mod partial;
+mod schema;
+mod serde;
+mod try_default;
 mod variant_default;
+mod versioned;
 
 /// Derive the [`core::default::Default`] trait.
 /// 
 /// This derive macro actually implements
-/// [`fancy_default::traits::Default`] to prevent naming pollution.
+/// `fancy_default::traits::Default` to prevent naming pollution.
 #[proc_macro_derive(Default, attributes(default))]
 pub fn derive_default(input: TokenStream) -> TokenStream {
     match default::process_default_derive(input.into()) {
@@ -19,7 +32,7 @@ pub fn derive_default(input: TokenStream) -> TokenStream {
     }
 }
 
-/// Derive the [`fancy_default::traits::ConstDefault`] trait.
+/// Derive the `fancy_default::traits::ConstDefault` trait.
 #[proc_macro_derive(ConstDefault, attributes(default))]
 pub fn derive_const_default(input: TokenStream) -> TokenStream {
     match default::process_const_default_derive(input.into()) {
@@ -36,3 +49,155 @@ pub fn derive_variant_default(input: TokenStream) -> TokenStream {
         Err(e) => e.to_compile_error().into(),
     }
 }
+
+/// Derive the `fancy_default::traits::DefaultSchema` trait.
+#[proc_macro_derive(DefaultSchema, attributes(default))]
+pub fn derive_default_schema(input: TokenStream) -> TokenStream {
+    match schema::process_default_schema(input.into()) {
+        Ok(ts) => ts.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// Implement the listed traits (`Default` if none is given),
+/// and document the default value of each field on the item itself.
+///
+/// Unlike the derive macros, this removes the `#[default]` helper attributes from the item.
+#[proc_macro_attribute]
+pub fn defaults(attr: TokenStream, item: TokenStream) -> TokenStream {
+    match attribute::process_defaults(attr.into(), item.into()) {
+        Ok(ts) => ts.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// Define structs whose fields may be written as `name: Type = <expr>`,
+/// and implement the traits listed in an optional `#[defaults(...)]` attribute.
+///
+/// This is a function-like macro since the compiler rejects this field syntax
+/// before any attribute macro could rewrite it.
+#[proc_macro]
+pub fn structure(input: TokenStream) -> TokenStream {
+    match attribute::process_structure(input.into()) {
+        Ok(ts) => ts.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// Derive a typestate builder, whose unset fields fall back to their default values.
+#[proc_macro_derive(DefaultBuilder, attributes(default))]
+pub fn derive_default_builder(input: TokenStream) -> TokenStream {
+    match builder::process_default_builder(input.into()) {
+        Ok(ts) => ts.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// Derive the `fancy_default::traits::DefaultWith` trait.
+#[proc_macro_derive(DefaultWith, attributes(default))]
+pub fn derive_default_with(input: TokenStream) -> TokenStream {
+    match default_with::process_default_with(input.into()) {
+        Ok(ts) => ts.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// Derive the `fancy_default::traits::AsyncDefault` trait.
+#[proc_macro_derive(AsyncDefault, attributes(default))]
+pub fn derive_async_default(input: TokenStream) -> TokenStream {
+    match async_default::process_async_default(input.into()) {
+        Ok(ts) => ts.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// Derive the `fancy_default::traits::DefaultPartial` trait,
+/// generating a `<Type>Partial` struct with every field optional.
+#[proc_macro_derive(DefaultPartial, attributes(default))]
+pub fn derive_default_partial(input: TokenStream) -> TokenStream {
+    match partial::process_default_partial(input.into()) {
+        Ok(ts) => ts.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// Derive the `fancy_default::traits::MergeDefaults` trait.
+#[proc_macro_derive(MergeDefaults, attributes(default))]
+pub fn derive_merge_defaults(input: TokenStream) -> TokenStream {
+    match merge::process_merge_defaults(input.into()) {
+        Ok(ts) => ts.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// Derive the `fancy_default::traits::DiffDefault` trait.
+#[proc_macro_derive(DiffDefault, attributes(default))]
+pub fn derive_diff_default(input: TokenStream) -> TokenStream {
+    match diff::process_diff_default(input.into()) {
+        Ok(ts) => ts.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// Derive `core::fmt::Debug`, listing only the fields that differ from their default values.
+#[proc_macro_derive(DebugNonDefault, attributes(default))]
+pub fn derive_debug_non_default(input: TokenStream) -> TokenStream {
+    match diff::process_debug_non_default(input.into()) {
+        Ok(ts) => ts.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// Derive `serde::Deserialize`, filling the missing fields with their default values.
+#[proc_macro_derive(Deserialize, attributes(default))]
+pub fn derive_deserialize(input: TokenStream) -> TokenStream {
+    match serde::process_deserialize(input.into()) {
+        Ok(ts) => ts.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// Derive `serde::Serialize`, omitting the fields equal to their default values.
+#[proc_macro_derive(Serialize, attributes(default))]
+pub fn derive_serialize(input: TokenStream) -> TokenStream {
+    match serde::process_serialize(input.into()) {
+        Ok(ts) => ts.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// Derive the `fancy_default::schemars::JsonSchemaDefaults` trait.
+#[proc_macro_derive(JsonSchemaDefaults, attributes(default))]
+pub fn derive_json_schema_defaults(input: TokenStream) -> TokenStream {
+    match serde::process_json_schema_defaults(input.into()) {
+        Ok(ts) => ts.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// Derive the `fancy_default::traits::ApplyOverrides` trait.
+#[proc_macro_derive(ApplyOverrides, attributes(default))]
+pub fn derive_apply_overrides(input: TokenStream) -> TokenStream {
+    match overrides::process_apply_overrides(input.into()) {
+        Ok(ts) => ts.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// Derive the `fancy_default::traits::VersionedDefault` trait.
+#[proc_macro_derive(VersionedDefault, attributes(default))]
+pub fn derive_versioned_default(input: TokenStream) -> TokenStream {
+    match versioned::process_versioned_default(input.into()) {
+        Ok(ts) => ts.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// Derive the `fancy_default::traits::TryDefault` trait.
+#[proc_macro_derive(TryDefault, attributes(default))]
+pub fn derive_try_default(input: TokenStream) -> TokenStream {
+    match try_default::process_try_default(input.into()) {
+        Ok(ts) => ts.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
@@ -0,0 +1,116 @@
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::{parse2, DeriveInput, Index, Member};
+
+use crate::crate_path::CrateRoot;
+use crate::default::struct_impl::FieldConfig;
+
+pub(crate) fn process_merge_defaults(input: TokenStream) -> syn::Result<TokenStream> {
+    let input: DeriveInput = parse2(input)?;
+    impl_merge_defaults(&input)
+}
+
+pub(crate) fn impl_merge_defaults(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let syn::Data::Struct(struct_data) = &input.data else {
+        return Err(syn::Error::new(
+            Span::call_site(),
+            "`MergeDefaults` is only supported on structs",
+        ));
+    };
+    let root = CrateRoot::of(input)?;
+    let fancy_default = root.path()?;
+
+    let fields = struct_data
+        .fields
+        .iter()
+        .enumerate()
+        .map(|(idx, field)| {
+            let config = FieldConfig::parse_attr(&field.attrs, &root)?;
+            if config.required {
+                return Err(syn::Error::new_spanned(
+                    field,
+                    "`required` fields are not supported by `MergeDefaults`",
+                ));
+            }
+
+            let member = match &field.ident {
+                Some(ident) => Member::Named(ident.clone()),
+                None => Member::Unnamed(Index::from(idx)),
+            };
+            Ok((member, &field.ty, config))
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    let merges = fields.iter().map(|(member, ty, config)| {
+        let cfg = &config.cfg_attrs;
+        let merge = match &config.nested {
+            // the field type's own default values apply, unless the field declares its own
+            Some(overrides) if overrides.is_empty() && !config.explicit => quote! {
+                #fancy_default::traits::MergeDefaults::merge_non_default(
+                    &mut self.#member,
+                    overlay.#member,
+                );
+            },
+            Some(_) => {
+                let default = config.default_value(ty);
+                quote! {
+                    let default: #ty = #default;
+                    #fancy_default::traits::MergeDefaults::merge_non_default_with(
+                        &mut self.#member,
+                        overlay.#member,
+                        &default,
+                    );
+                }
+            }
+            None => {
+                let default = config.default_value(ty);
+                quote! {
+                    let default: #ty = #default;
+                    if overlay.#member != default {
+                        self.#member = overlay.#member;
+                    }
+                }
+            }
+        };
+        quote! { #(#cfg)* { #merge } }
+    });
+
+    let merges_with = fields.iter().map(|(member, _, config)| {
+        let cfg = &config.cfg_attrs;
+        if config.nested.is_some() {
+            quote! {
+                #(#cfg)*
+                #fancy_default::traits::MergeDefaults::merge_non_default_with(
+                    &mut self.#member,
+                    overlay.#member,
+                    &default.#member,
+                );
+            }
+        } else {
+            quote! {
+                #(#cfg)*
+                if overlay.#member != default.#member {
+                    self.#member = overlay.#member;
+                }
+            }
+        }
+    });
+
+    let name = &input.ident;
+    let (impl_g, type_g, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_g #fancy_default::traits::MergeDefaults for #name #type_g #where_clause {
+            fn merge_non_default(&mut self, overlay: Self) {
+                let _ = &overlay;
+                #(#merges)*
+            }
+
+            fn merge_non_default_with(&mut self, overlay: Self, default: &Self) {
+                let _ = (&overlay, default);
+                #(#merges_with)*
+            }
+        }
+    })
+}
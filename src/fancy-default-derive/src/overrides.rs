@@ -0,0 +1,86 @@
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::{parse2, DeriveInput, Index, Member};
+
+use crate::crate_path::CrateRoot;
+use crate::default::struct_impl::FieldConfig;
+
+pub(crate) fn process_apply_overrides(input: TokenStream) -> syn::Result<TokenStream> {
+    let input: DeriveInput = parse2(input)?;
+    impl_apply_overrides(&input)
+}
+
+pub(crate) fn impl_apply_overrides(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let syn::Data::Struct(struct_data) = &input.data else {
+        return Err(syn::Error::new(
+            Span::call_site(),
+            "`ApplyOverrides` is only supported on structs",
+        ));
+    };
+    let root = CrateRoot::of(input)?;
+    let fancy_default = root.path()?;
+
+    let arms = struct_data
+        .fields
+        .iter()
+        .enumerate()
+        .map(|(idx, field)| {
+            let config = FieldConfig::parse_attr(&field.attrs, &root)?;
+            let (member, key) = match &field.ident {
+                Some(ident) => {
+                    let key = ident.to_string();
+                    let key = key.strip_prefix("r#").unwrap_or(&key).to_owned();
+                    (Member::Named(ident.clone()), key)
+                }
+                None => (Member::Unnamed(Index::from(idx)), idx.to_string()),
+            };
+
+            let cfg = &config.cfg_attrs;
+            Ok(if config.nested.is_some() {
+                quote! {
+                    #(#cfg)*
+                    (#key, ::core::option::Option::Some(rest)) => {
+                        #fancy_default::traits::ApplyOverrides::apply(&mut self.#member, rest, value)
+                            .map_err(|error| error.nested(#key))
+                    }
+                }
+            } else {
+                quote! {
+                    #(#cfg)*
+                    (#key, ::core::option::Option::None) => {
+                        self.#member = #fancy_default::__private::parse_override(key, value)?;
+                        ::core::result::Result::Ok(())
+                    }
+                }
+            })
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    let name = &input.ident;
+    let (impl_g, type_g, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_g #fancy_default::traits::ApplyOverrides for #name #type_g #where_clause {
+            fn apply(
+                &mut self,
+                key: &str,
+                value: &str,
+            ) -> ::core::result::Result<(), #fancy_default::traits::OverrideError> {
+                let _ = value;
+                let (head, rest) = match key.split_once('.') {
+                    ::core::option::Option::Some((head, rest)) => (head, ::core::option::Option::Some(rest)),
+                    ::core::option::Option::None => (key, ::core::option::Option::None),
+                };
+                match (head, rest) {
+                    #(#arms)*
+                    _ => ::core::result::Result::Err(
+                        #fancy_default::traits::OverrideError::UnknownKey(
+                            ::core::convert::Into::into(key),
+                        ),
+                    ),
+                }
+            }
+        }
+    })
+}
@@ -0,0 +1,190 @@
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::{parse2, Attribute, DeriveInput, Fields, Index, Member};
+
+use crate::crate_path::CrateRoot;
+use crate::default::struct_impl::{FieldConfig, StructConfig};
+
+pub(crate) fn process_default_partial(input: TokenStream) -> syn::Result<TokenStream> {
+    let input: DeriveInput = parse2(input)?;
+    impl_default_partial(&input)
+}
+
+pub(crate) fn impl_default_partial(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let syn::Data::Struct(struct_data) = &input.data else {
+        return Err(syn::Error::new(
+            Span::call_site(),
+            "`DefaultPartial` is only supported on structs",
+        ));
+    };
+
+    let name = &input.ident;
+    let vis = &input.vis;
+    let partial_name = format_ident!("{}Partial", name);
+    let config = StructConfig::parse_attr(&input.attrs)?;
+    let root = CrateRoot::of(input)?;
+    let fancy_default = root.path()?;
+
+    let mut defs = Vec::new();
+    let mut empty = Vec::new();
+    let mut merge = Vec::new();
+    let mut apply = Vec::new();
+    let mut resolve = Vec::new();
+    for (idx, field) in struct_data.fields.iter().enumerate() {
+        let field_config = FieldConfig::parse_attr(&field.attrs, &root)?;
+        if field_config.required {
+            return Err(syn::Error::new_spanned(
+                field,
+                "`required` fields are not supported by `DefaultPartial`",
+            ));
+        }
+
+        let member = match &field.ident {
+            Some(ident) => Member::Named(ident.clone()),
+            None => Member::Unnamed(Index::from(idx)),
+        };
+        let ty = &field.ty;
+        let field_vis = &field.vis;
+        let docs = field.attrs.iter().filter(|a| a.path().is_ident("doc"));
+        let cfg = &field_config.cfg_attrs;
+        let default = field_config.default_value(ty);
+
+        let partial_ty = if field_config.nested.is_some() {
+            // an empty layer of the field type resolves to its own defaults
+            let default = if field_config.explicit {
+                default
+            } else {
+                field_config.default_value_from(
+                    ty,
+                    quote! {
+                        #fancy_default::traits::Partial::resolve(
+                            <<#ty as #fancy_default::traits::DefaultPartial>::Partial
+                                as ::core::default::Default>::default()
+                        )
+                    },
+                )
+            };
+            empty.push(quote! { #(#cfg)* #member: ::core::default::Default::default() });
+            merge.push(quote! {
+                #(#cfg)*
+                #member: #fancy_default::traits::Partial::merge(self.#member, other.#member)
+            });
+            apply.push(quote! {
+                #(#cfg)*
+                {
+                    base.#member = #fancy_default::traits::Partial::apply(self.#member, base.#member);
+                }
+            });
+            resolve.push(quote! {
+                #(#cfg)*
+                #member: #fancy_default::traits::Partial::apply(self.#member, #default)
+            });
+            quote! { <#ty as #fancy_default::traits::DefaultPartial>::Partial }
+        } else {
+            empty.push(quote! { #(#cfg)* #member: ::core::option::Option::None });
+            merge.push(quote! {
+                #(#cfg)*
+                #member: ::core::option::Option::or(other.#member, self.#member)
+            });
+            apply.push(quote! {
+                #(#cfg)*
+                if let ::core::option::Option::Some(value) = self.#member {
+                    base.#member = value;
+                }
+            });
+            resolve.push(quote! {
+                #(#cfg)*
+                #member: match self.#member {
+                    ::core::option::Option::Some(value) => value,
+                    ::core::option::Option::None => #default,
+                }
+            });
+            quote! { ::core::option::Option<#ty> }
+        };
+
+        defs.push(match &field.ident {
+            Some(ident) => quote! { #(#cfg)* #(#docs)* #field_vis #ident: #partial_ty },
+            None => quote! { #(#cfg)* #(#docs)* #field_vis #partial_ty },
+        });
+    }
+
+    let generics = &input.generics;
+    let (impl_g, type_g, where_clause) = generics.split_for_impl();
+    let definition = match &struct_data.fields {
+        Fields::Named(_) => quote! { #generics #where_clause { #(#defs,)* } },
+        Fields::Unnamed(_) => quote! { #generics (#(#defs,)*) #where_clause; },
+        Fields::Unit => quote! { #generics #where_clause; },
+    };
+
+    let derive = partial_derive(&config);
+    let doc = format!(
+        "A partial [`{}`], with every field optional, generated by `DefaultPartial`.",
+        name
+    );
+
+    Ok(quote! {
+        #[doc = #doc]
+        #derive
+        #vis struct #partial_name #definition
+
+        impl #impl_g ::core::default::Default for #partial_name #type_g #where_clause {
+            fn default() -> Self {
+                #partial_name {
+                    #(#empty,)*
+                }
+            }
+        }
+
+        impl #impl_g #partial_name #type_g #where_clause {
+            /// Merge two layers, where the fields set in `other` take precedence.
+            #vis fn merge(self, other: Self) -> Self {
+                #partial_name {
+                    #(#merge,)*
+                }
+            }
+
+            /// Override the fields of `base` that are set in this layer.
+            #vis fn apply(self, base: #name #type_g) -> #name #type_g {
+                #[allow(unused_mut)]
+                let mut base = base;
+                #(#apply)*
+                base
+            }
+
+            /// Fill the fields that are not set with their default values.
+            #vis fn resolve(self) -> #name #type_g {
+                #name {
+                    #(#resolve,)*
+                }
+            }
+        }
+
+        #[automatically_derived]
+        impl #impl_g #fancy_default::traits::Partial for #partial_name #type_g #where_clause {
+            type Target = #name #type_g;
+
+            fn merge(self, other: Self) -> Self {
+                #partial_name::merge(self, other)
+            }
+
+            fn apply(self, base: Self::Target) -> Self::Target {
+                #partial_name::apply(self, base)
+            }
+
+            fn resolve(self) -> Self::Target {
+                #partial_name::resolve(self)
+            }
+        }
+
+        #[automatically_derived]
+        impl #impl_g #fancy_default::traits::DefaultPartial for #name #type_g #where_clause {
+            type Partial = #partial_name #type_g;
+        }
+    })
+}
+
+/// `#[derive(...)]` of the partial struct, from `#[default(partial(derive(...)))]`.
+fn partial_derive(config: &StructConfig) -> Option<Attribute> {
+    let derive = &config.partial_derive;
+    (!derive.is_empty()).then(|| syn::parse_quote! { #[derive(#(#derive),*)] })
+}
@@ -0,0 +1,130 @@
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::quote;
+use syn::{parse2, Attribute, DeriveInput, Expr, ExprLit, Fields, Generics, Lit, Meta};
+
+use crate::crate_path::CrateRoot;
+use crate::default::{enum_impl, struct_impl::FieldConfig};
+
+pub(crate) fn process_default_schema(input: TokenStream) -> syn::Result<TokenStream> {
+    let input: DeriveInput = parse2(input)?;
+    enum_impl::expand_cfg_variants(&input, impl_default_schema)
+}
+
+pub(crate) fn impl_default_schema(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let root = CrateRoot::of(input)?;
+    let fancy_default = root.path()?;
+    let (fields, variant) = match &input.data {
+        syn::Data::Struct(struct_data) => {
+            (impl_fields(&struct_data.fields, &root, &fancy_default)?, None)
+        }
+        syn::Data::Enum(enum_data) => {
+            let default = enum_impl::select_default_variant(&input.attrs, enum_data)?;
+            let fields = if default.expr.is_some() {
+                // the payload is replaced as a whole, so the field defaults do not apply
+                Vec::new()
+            } else {
+                impl_fields(&default.variant.fields, &root, &fancy_default)?
+            };
+            (fields, Some(default.variant.ident.to_string()))
+        }
+        syn::Data::Union(_) => {
+            return Err(syn::Error::new(
+                Span::call_site(),
+                "`DefaultSchema` is not supported on unions",
+            ))
+        }
+    };
+
+    Ok(impl_schema(
+        &input.ident,
+        &input.generics,
+        &fields,
+        variant,
+        &fancy_default,
+    ))
+}
+
+fn impl_fields(
+    fields: &Fields,
+    root: &CrateRoot,
+    fancy_default: &TokenStream,
+) -> syn::Result<Vec<TokenStream>> {
+    fields
+        .iter()
+        .enumerate()
+        .map(|(idx, field)| {
+            let config = FieldConfig::parse_attr(&field.attrs, root)?;
+            let name = field
+                .ident
+                .as_ref()
+                .map_or_else(|| idx.to_string(), |i| i.to_string());
+            let ty = &field.ty;
+            let expr = if config.required {
+                String::new()
+            } else {
+                config.expr_string()
+            };
+            let required = config.required;
+            let doc = doc_string(&field.attrs);
+            let cfg = &config.cfg_attrs;
+
+            Ok(quote! {
+                #(#cfg)*
+                #fancy_default::__private::field_default(
+                    #name,
+                    ::core::stringify!(#ty),
+                    #expr,
+                    #required,
+                    #doc,
+                )
+            })
+        })
+        .collect()
+}
+
+fn impl_schema(
+    name: &Ident,
+    generics: &Generics,
+    fields: &[TokenStream],
+    variant: Option<String>,
+    fancy_default: &TokenStream,
+) -> TokenStream {
+    let (impl_g, type_g, where_clause) = generics.split_for_impl();
+    let variant = variant.map(|v| {
+        quote! {
+            fn default_variant() -> ::core::option::Option<&'static str> {
+                ::core::option::Option::Some(#v)
+            }
+        }
+    });
+
+    quote! {
+        #[automatically_derived]
+        impl #impl_g #fancy_default::traits::DefaultSchema for #name #type_g #where_clause {
+            fn default_schema() -> &'static [#fancy_default::traits::FieldDefault] {
+                const FIELDS: &[#fancy_default::traits::FieldDefault] = &[#(#fields),*];
+                FIELDS
+            }
+
+            #variant
+        }
+    }
+}
+
+/// Collect the `#[doc = "..."]` attributes, one line each.
+pub(crate) fn doc_string(attrs: &[Attribute]) -> String {
+    attrs
+        .iter()
+        .filter_map(|attr| match &attr.meta {
+            Meta::NameValue(nv) if nv.path.is_ident("doc") => match &nv.value {
+                Expr::Lit(ExprLit {
+                    lit: Lit::Str(s), ..
+                }) => Some(s.value()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .map(|line| line.strip_prefix(' ').map(str::to_owned).unwrap_or(line))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
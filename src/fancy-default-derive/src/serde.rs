@@ -0,0 +1,466 @@
+use itertools::Itertools;
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::{parse2, parse_quote, DeriveInput, Fields, GenericParam, LifetimeParam, Type};
+
+use crate::crate_path::CrateRoot;
+use crate::default::{
+    enum_impl,
+    struct_impl::{FieldConfig, StructConfig},
+};
+
+pub(crate) fn process_serialize(input: TokenStream) -> syn::Result<TokenStream> {
+    let input: DeriveInput = parse2(input)?;
+    impl_serialize(&input)
+}
+
+pub(crate) fn process_deserialize(input: TokenStream) -> syn::Result<TokenStream> {
+    let input: DeriveInput = parse2(input)?;
+    impl_deserialize(&input)
+}
+
+/// A named field with its configuration and serialized name.
+pub(crate) struct SerdeField<'a> {
+    pub(crate) ident: &'a Ident,
+    pub(crate) ty: &'a Type,
+    pub(crate) config: FieldConfig,
+    pub(crate) name: String,
+}
+
+/// The named fields of a struct, with the names given by `rename` and `rename_all`.
+pub(crate) fn serde_fields<'a>(
+    input: &'a DeriveInput,
+    config: &StructConfig,
+    derive: &str,
+) -> syn::Result<Vec<SerdeField<'a>>> {
+    let syn::Data::Struct(struct_data) = &input.data else {
+        return Err(syn::Error::new(
+            Span::call_site(),
+            format!("`{}` is only supported on structs", derive),
+        ));
+    };
+    let Fields::Named(fields) = &struct_data.fields else {
+        return Err(syn::Error::new(
+            Span::call_site(),
+            format!("`{}` is only supported on structs with named fields", derive),
+        ));
+    };
+    let root = CrateRoot::of(input)?;
+
+    fields
+        .named
+        .iter()
+        .map(|field| {
+            let ident = field
+                .ident
+                .as_ref()
+                .unwrap_or_else(|| panic!("unexpected internal error: unnamed field"));
+            let field_config = FieldConfig::parse_attr(&field.attrs, &root)?;
+            let name = match (&field_config.rename, config.rename_all) {
+                (Some(rename), _) => rename.value(),
+                (None, rule) => {
+                    let name = ident.to_string();
+                    let name = name.strip_prefix("r#").unwrap_or(&name);
+                    match rule {
+                        Some(rule) => rule.apply(name),
+                        None => name.to_owned(),
+                    }
+                }
+            };
+            Ok(SerdeField {
+                ident,
+                ty: &field.ty,
+                config: field_config,
+                name,
+            })
+        })
+        .collect()
+}
+
+pub(crate) fn impl_deserialize(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let config = StructConfig::parse_attr(&input.attrs)?;
+    let fancy_default = CrateRoot::of(input)?.path()?;
+    let fields = serde_fields(input, &config, "Deserialize")?;
+
+    let name = &input.ident;
+    let name_str = name.to_string();
+    let expecting = format!("struct {}", name);
+
+    let index = (0..fields.len())
+        .map(|idx| format_ident!("__field{}", idx))
+        .collect::<Vec<_>>();
+    let (ident, ty, key, cfg): (Vec<_>, Vec<_>, Vec<_>, Vec<_>) = fields
+        .iter()
+        .map(|f| (f.ident, f.ty, f.name.as_str(), &f.config.cfg_attrs))
+        .multiunzip();
+
+    let unknown = if config.deny_unknown_fields {
+        quote! {
+            ::core::result::Result::Err(
+                #fancy_default::__private::serde::de::Error::unknown_field(value, FIELDS)
+            )
+        }
+    } else {
+        quote! { ::core::result::Result::Ok(__Field::__ignore) }
+    };
+    let unknown_index = if config.deny_unknown_fields {
+        quote! {{
+            // the number of fields depends on the `#[cfg]` attributes of the fields
+            struct __ExpectedIndex;
+
+            impl de::Expected for __ExpectedIndex {
+                fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                    ::core::write!(f, "field index 0 <= i < {}", FIELDS.len())
+                }
+            }
+
+            ::core::result::Result::Err(de::Error::invalid_value(
+                de::Unexpected::Unsigned(value),
+                &__ExpectedIndex,
+            ))
+        }}
+    } else {
+        quote! { ::core::result::Result::Ok(__Field::__ignore) }
+    };
+    let unknown_bytes = if config.deny_unknown_fields {
+        quote! {
+            ::core::result::Result::Err(de::Error::invalid_value(
+                de::Unexpected::Bytes(value),
+                &self,
+            ))
+        }
+    } else {
+        quote! { ::core::result::Result::Ok(__Field::__ignore) }
+    };
+
+    let fallback = fields
+        .iter()
+        .map(|field| {
+            if field.config.required {
+                let key = &field.name;
+                quote! {
+                    return ::core::result::Result::Err(
+                        #fancy_default::__private::serde::de::Error::missing_field(#key)
+                    )
+                }
+            } else {
+                field.config.default_value(field.ty)
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let mut generics = input.generics.clone();
+    for param in generics.params.iter_mut() {
+        if let GenericParam::Type(param) = param {
+            param
+                .bounds
+                .push(parse_quote! { #fancy_default::__private::serde::Deserialize<'de> });
+        }
+    }
+    let (_, type_g, where_clause) = input.generics.split_for_impl();
+    // `'de` outlives the lifetimes of the struct, so that its fields can borrow from the input
+    let mut de_lifetime: LifetimeParam = parse_quote! { 'de };
+    de_lifetime
+        .bounds
+        .extend(input.generics.lifetimes().map(|l| l.lifetime.clone()));
+    let mut de_generics = generics.clone();
+    de_generics.params.insert(0, GenericParam::Lifetime(de_lifetime));
+    let (de_impl_g, de_type_g, _) = de_generics.split_for_impl();
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #de_impl_g #fancy_default::__private::serde::Deserialize<'de> for #name #type_g #where_clause {
+            fn deserialize<__D>(deserializer: __D) -> ::core::result::Result<Self, __D::Error>
+            where
+                __D: #fancy_default::__private::serde::Deserializer<'de>,
+            {
+                use #fancy_default::__private::serde::de;
+
+                const FIELDS: &[&str] = &[#(#(#cfg)* #key),*];
+
+                #[allow(non_camel_case_types, dead_code)]
+                enum __Field {
+                    #(#(#cfg)* #index,)*
+                    __ignore,
+                }
+
+                struct __FieldVisitor;
+
+                impl<'de> de::Visitor<'de> for __FieldVisitor {
+                    type Value = __Field;
+
+                    fn expecting(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                        f.write_str("field identifier")
+                    }
+
+                    fn visit_str<__E: de::Error>(self, value: &str) -> ::core::result::Result<__Field, __E> {
+                        match value {
+                            #(#(#cfg)* #key => ::core::result::Result::Ok(__Field::#index),)*
+                            _ => #unknown,
+                        }
+                    }
+
+                    fn visit_u64<__E: de::Error>(self, value: u64) -> ::core::result::Result<__Field, __E> {
+                        // the fields are looked up by name, as their indices depend on `#[cfg]`
+                        let key = <usize as ::core::convert::TryFrom<u64>>::try_from(value)
+                            .ok()
+                            .and_then(|idx| FIELDS.get(idx));
+                        match key {
+                            ::core::option::Option::Some(key) => self.visit_str(key),
+                            ::core::option::Option::None => #unknown_index,
+                        }
+                    }
+
+                    fn visit_bytes<__E: de::Error>(self, value: &[u8]) -> ::core::result::Result<__Field, __E> {
+                        match ::core::str::from_utf8(value) {
+                            ::core::result::Result::Ok(value) => self.visit_str(value),
+                            ::core::result::Result::Err(_) => #unknown_bytes,
+                        }
+                    }
+                }
+
+                impl<'de> de::Deserialize<'de> for __Field {
+                    fn deserialize<__D>(deserializer: __D) -> ::core::result::Result<Self, __D::Error>
+                    where
+                        __D: de::Deserializer<'de>,
+                    {
+                        deserializer.deserialize_identifier(__FieldVisitor)
+                    }
+                }
+
+                struct __Visitor #de_impl_g #where_clause {
+                    marker: ::core::marker::PhantomData<fn() -> #name #type_g>,
+                    lifetime: ::core::marker::PhantomData<&'de ()>,
+                }
+
+                impl #de_impl_g de::Visitor<'de> for __Visitor #de_type_g #where_clause {
+                    type Value = #name #type_g;
+
+                    fn expecting(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                        f.write_str(#expecting)
+                    }
+
+                    fn visit_seq<__A>(self, mut seq: __A) -> ::core::result::Result<Self::Value, __A::Error>
+                    where
+                        __A: de::SeqAccess<'de>,
+                    {
+                        #(
+                            #(#cfg)*
+                            let #index: ::core::option::Option<#ty> = seq.next_element()?;
+                        )*
+                        #(
+                            #(#cfg)*
+                            let #ident: #ty = match #index {
+                                ::core::option::Option::Some(value) => value,
+                                ::core::option::Option::None => #fallback,
+                            };
+                        )*
+                        ::core::result::Result::Ok(#name { #(#(#cfg)* #ident,)* })
+                    }
+
+                    fn visit_map<__A>(self, mut map: __A) -> ::core::result::Result<Self::Value, __A::Error>
+                    where
+                        __A: de::MapAccess<'de>,
+                    {
+                        #(
+                            #(#cfg)*
+                            let mut #index: ::core::option::Option<#ty> = ::core::option::Option::None;
+                        )*
+                        while let ::core::option::Option::Some(key) = map.next_key::<__Field>()? {
+                            match key {
+                                #(
+                                    #(#cfg)*
+                                    __Field::#index => {
+                                        if #index.is_some() {
+                                            return ::core::result::Result::Err(
+                                                <__A::Error as de::Error>::duplicate_field(#key)
+                                            );
+                                        }
+                                        #index = ::core::option::Option::Some(map.next_value()?);
+                                    }
+                                )*
+                                __Field::__ignore => {
+                                    let _ = map.next_value::<de::IgnoredAny>()?;
+                                }
+                            }
+                        }
+                        #(
+                            #(#cfg)*
+                            let #ident: #ty = match #index {
+                                ::core::option::Option::Some(value) => value,
+                                ::core::option::Option::None => #fallback,
+                            };
+                        )*
+                        ::core::result::Result::Ok(#name { #(#(#cfg)* #ident,)* })
+                    }
+                }
+
+                deserializer.deserialize_struct(
+                    #name_str,
+                    FIELDS,
+                    __Visitor {
+                        marker: ::core::marker::PhantomData,
+                        lifetime: ::core::marker::PhantomData,
+                    },
+                )
+            }
+        }
+    })
+}
+
+pub(crate) fn impl_serialize(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let config = StructConfig::parse_attr(&input.attrs)?;
+    let fancy_default = CrateRoot::of(input)?.path()?;
+    let fields = serde_fields(input, &config, "Serialize")?;
+    let skip_defaults = config.skip_defaults.unwrap_or(true);
+
+    let name = &input.ident;
+    let name_str = name.to_string();
+
+    let (skip, entry): (Vec<_>, Vec<_>) = fields
+        .iter()
+        .enumerate()
+        .map(|(idx, field)| {
+            let ident = field.ident;
+            let key = &field.name;
+            let flag = format_ident!("__skip{}", idx);
+            let cfg = &field.config.cfg_attrs;
+            let skip = if skip_defaults && !field.config.required {
+                let ty = field.ty;
+                let default = field.config.default_value(ty);
+                quote! {
+                    #(#cfg)*
+                    let #flag = {
+                        let default: #ty = #default;
+                        self.#ident == default
+                    };
+                }
+            } else {
+                quote! {
+                    #(#cfg)*
+                    let #flag = false;
+                }
+            };
+            let entry = quote! {
+                #(#cfg)*
+                if #flag {
+                    state.skip_field(#key)?;
+                } else {
+                    state.serialize_field(#key, &self.#ident)?;
+                }
+            };
+            (skip, entry)
+        })
+        .unzip();
+    let count = fields.iter().enumerate().map(|(idx, field)| {
+        let flag = format_ident!("__skip{}", idx);
+        let cfg = &field.config.cfg_attrs;
+        quote! {
+            #(#cfg)*
+            {
+                len += !#flag as usize;
+            }
+        }
+    });
+
+    let mut generics = input.generics.clone();
+    for param in generics.params.iter_mut() {
+        if let GenericParam::Type(param) = param {
+            param
+                .bounds
+                .push(parse_quote! { #fancy_default::__private::serde::Serialize });
+        }
+    }
+    let (impl_g, _, _) = generics.split_for_impl();
+    let (_, type_g, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_g #fancy_default::__private::serde::Serialize for #name #type_g #where_clause {
+            fn serialize<__S>(&self, serializer: __S) -> ::core::result::Result<__S::Ok, __S::Error>
+            where
+                __S: #fancy_default::__private::serde::Serializer,
+            {
+                use #fancy_default::__private::serde::ser::SerializeStruct;
+
+                #(#skip)*
+                #[allow(unused_mut)]
+                let mut len = 0;
+                #(#count)*
+                let mut state = serializer.serialize_struct(#name_str, len)?;
+                #(#entry)*
+                state.end()
+            }
+        }
+    })
+}
+
+pub(crate) fn process_json_schema_defaults(input: TokenStream) -> syn::Result<TokenStream> {
+    let input: DeriveInput = parse2(input)?;
+    enum_impl::expand_cfg_variants(&input, impl_json_schema_defaults)
+}
+
+pub(crate) fn impl_json_schema_defaults(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+    let root = CrateRoot::of(input)?;
+    let fancy_default = root.path()?;
+
+    let body = match &input.data {
+        syn::Data::Enum(enum_data) => {
+            let block =
+                enum_impl::select_default_variant(&input.attrs, enum_data)?.to_block(name, &root)?;
+            quote! {
+                let default: Self = #block;
+                match #fancy_default::__private::serde_json::to_value(&default) {
+                    ::core::result::Result::Ok(value) => {
+                        #fancy_default::schemars::JsonDefault::Value(value)
+                    }
+                    ::core::result::Result::Err(_) => {
+                        #fancy_default::schemars::JsonDefault::Unavailable
+                    }
+                }
+            }
+        }
+        _ => {
+            let config = StructConfig::parse_attr(&input.attrs)?;
+            let fields = serde_fields(input, &config, "JsonSchemaDefaults")?;
+            let entries = fields
+                .iter()
+                .filter(|field| !field.config.required)
+                .map(|field| {
+                    let ty = field.ty;
+                    let key = &field.name;
+                    let default = field.config.default_value(ty);
+                    let cfg = &field.config.cfg_attrs;
+                    quote! {
+                        #(#cfg)*
+                        {
+                            let default: #ty = #default;
+                            if let ::core::result::Result::Ok(value) =
+                                #fancy_default::__private::serde_json::to_value(&default)
+                            {
+                                fields.push((#key, value));
+                            }
+                        }
+                    }
+                });
+            quote! {
+                #[allow(unused_mut)]
+                let mut fields = #fancy_default::__private::Vec::new();
+                #(#entries)*
+                #fancy_default::schemars::JsonDefault::Fields(fields)
+            }
+        }
+    };
+
+    let (impl_g, type_g, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_g #fancy_default::schemars::JsonSchemaDefaults for #name #type_g #where_clause {
+            fn json_defaults() -> #fancy_default::schemars::JsonDefault {
+                #body
+            }
+        }
+    })
+}
@@ -0,0 +1,282 @@
+use itertools::Itertools;
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::{parse2, parse_quote, DeriveInput, Fields, Generics, Type, Visibility};
+
+use crate::crate_path::CrateRoot;
+use crate::default::{enum_impl, struct_impl::FieldConfig};
+
+pub(crate) fn process_try_default(input: TokenStream) -> syn::Result<TokenStream> {
+    let input: DeriveInput = parse2(input)?;
+    enum_impl::expand_cfg_variants(&input, impl_try_default)
+}
+
+pub(crate) fn impl_try_default(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+    let error_name = format_ident!("{}DefaultError", name);
+    let root = CrateRoot::of(input)?;
+    let fancy_default = root.path()?;
+
+    let (body, error) = match &input.data {
+        syn::Data::Struct(struct_data) => impl_fields(
+            &struct_data.fields,
+            quote! { #name },
+            name,
+            &error_name,
+            &input.vis,
+            &input.generics,
+            &root,
+        )?,
+        syn::Data::Enum(enum_data) => {
+            let default = enum_impl::select_default_variant(&input.attrs, enum_data)?;
+            match &default.expr {
+                Some(expr) => (quote! { ::core::result::Result::Ok(#expr) }, None),
+                None => {
+                    let variant_name = &default.variant.ident;
+                    impl_fields(
+                        &default.variant.fields,
+                        quote! { #name::#variant_name },
+                        name,
+                        &error_name,
+                        &input.vis,
+                        &input.generics,
+                        &root,
+                    )?
+                }
+            }
+        }
+        syn::Data::Union(_) => {
+            return Err(syn::Error::new(
+                Span::call_site(),
+                "`TryDefault` is not supported on unions",
+            ))
+        }
+    };
+
+    let (impl_g, type_g, where_clause) = input.generics.split_for_impl();
+    let error_ty = match &error {
+        Some(_) => quote! { #error_name #type_g },
+        None => quote! { ::core::convert::Infallible },
+    };
+
+    Ok(quote! {
+        #error
+
+        #[automatically_derived]
+        impl #impl_g #fancy_default::traits::TryDefault for #name #type_g #where_clause {
+            type Error = #error_ty;
+
+            fn try_default() -> ::core::result::Result<Self, Self::Error> {
+                #body
+            }
+        }
+    })
+}
+
+/// Generate the body of `try_default`,
+/// and the error type if any of the fields is fallible.
+fn impl_fields(
+    fields: &Fields,
+    path: TokenStream,
+    name: &Ident,
+    error_name: &Ident,
+    vis: &Visibility,
+    generics: &Generics,
+    root: &CrateRoot,
+) -> syn::Result<(TokenStream, Option<TokenStream>)> {
+    let named = matches!(fields, Fields::Named(_));
+    let fields = fields
+        .iter()
+        .enumerate()
+        .map(|(idx, field)| {
+            let ident = field
+                .ident
+                .clone()
+                .unwrap_or_else(|| format_ident!("field_{}", idx));
+            let config = FieldConfig::parse_attr(&field.attrs, root)?;
+            if config.required {
+                return Err(syn::Error::new_spanned(
+                    field,
+                    "`required` fields are not supported by `TryDefault`",
+                ));
+            }
+            Ok((ident, &field.ty, config))
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    let values = fields.iter().map(|(ident, ty, config)| {
+        let cfg = &config.cfg_attrs;
+        match &config.try_expr {
+            Some((expr, error)) => {
+                let error = error_type(ty, error.as_ref());
+                quote! {
+                    #(#cfg)*
+                    let #ident: ::core::result::Result<#ty, #error> = #expr;
+                }
+            }
+            None => {
+                let expr = config.default_value(ty);
+                quote! {
+                    #(#cfg)*
+                    let #ident: #ty = #expr;
+                }
+            }
+        }
+    });
+
+    let idents = fields
+        .iter()
+        .map(|(ident, _, config)| {
+            let cfg = &config.cfg_attrs;
+            quote! { #(#cfg)* #ident }
+        })
+        .collect::<Vec<_>>();
+    let construct = if named {
+        quote! { #path { #(#idents,)* } }
+    } else if idents.is_empty() {
+        path
+    } else {
+        quote! { #path(#(#idents),*) }
+    };
+
+    let fallible = fields
+        .iter()
+        .filter(|(_, _, config)| config.try_expr.is_some())
+        .collect::<Vec<_>>();
+
+    if fallible.is_empty() {
+        return Ok((
+            quote! {
+                #(#values)*
+                ::core::result::Result::Ok(#construct)
+            },
+            None,
+        ));
+    }
+
+    let (try_ident, try_error, try_cfg): (Vec<_>, Vec<_>, Vec<_>) = fallible
+        .iter()
+        .map(|(ident, ty, config)| {
+            let error = config.try_expr.as_ref().and_then(|(_, e)| e.as_ref());
+            (ident, error_type(ty, error), &config.cfg_attrs)
+        })
+        .multiunzip();
+    let try_name = try_ident.iter().map(|i| i.to_string()).collect::<Vec<_>>();
+
+    let fancy_default = root.path()?;
+
+    // The error struct carries the generics of the type,
+    // and a marker field so that the ones not used by any error type stay used.
+    let (impl_g, type_g, where_clause) = generics.split_for_impl();
+    let mut debug_generics = generics.clone();
+    let mut display_generics = generics.clone();
+    // the error types of gated fields may not exist, so they are left unbounded
+    for (error, _) in try_error.iter().zip(&try_cfg).filter(|(_, cfg)| cfg.is_empty()) {
+        debug_generics
+            .make_where_clause()
+            .predicates
+            .push(parse_quote! { #error: ::core::fmt::Debug });
+        display_generics
+            .make_where_clause()
+            .predicates
+            .push(parse_quote! { #error: ::core::fmt::Display });
+    }
+    let mut error_generics = generics.clone();
+    error_generics
+        .make_where_clause()
+        .predicates
+        .push(parse_quote! { Self: ::core::fmt::Debug + ::core::fmt::Display });
+    let debug_where = &debug_generics.where_clause;
+    let display_where = &display_generics.where_clause;
+    let error_where = &error_generics.where_clause;
+    let (marker_field, marker_value) = if generics.params.is_empty() {
+        (None, None)
+    } else {
+        (
+            Some(quote! {
+                #[doc(hidden)]
+                pub __marker: ::core::marker::PhantomData<fn() -> #name #type_g>,
+            }),
+            Some(quote! { __marker: ::core::marker::PhantomData, }),
+        )
+    };
+
+    let body = quote! {
+        #(#values)*
+
+        #[allow(unused_mut)]
+        let mut __failed = false;
+        #(
+            #(#try_cfg)*
+            {
+                __failed |= #try_ident.is_err();
+            }
+        )*
+        if __failed {
+            return ::core::result::Result::Err(#error_name {
+                #(#(#try_cfg)* #try_ident: #try_ident.err(),)*
+                #marker_value
+            });
+        }
+        #(
+            #(#try_cfg)*
+            let #try_ident = match #try_ident {
+                ::core::result::Result::Ok(value) => value,
+                ::core::result::Result::Err(_) => ::core::unreachable!(),
+            };
+        )*
+        ::core::result::Result::Ok(#construct)
+    };
+
+    let doc = format!("The error returned by the `TryDefault` impl of [`{}`].", name);
+    let message = format!("failed to create the default value of `{}`", name);
+    let error_str = error_name.to_string();
+    let error = quote! {
+        #[doc = #doc]
+        ///
+        /// Each field holds the error of the corresponding field, if it failed.
+        #vis struct #error_name #generics #where_clause {
+            #(#(#try_cfg)* pub #try_ident: ::core::option::Option<#try_error>,)*
+            #marker_field
+        }
+
+        impl #impl_g ::core::fmt::Debug for #error_name #type_g #debug_where {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                let mut debug = f.debug_struct(#error_str);
+                #(
+                    #(#try_cfg)*
+                    debug.field(#try_name, &self.#try_ident);
+                )*
+                debug.finish()
+            }
+        }
+
+        impl #impl_g ::core::fmt::Display for #error_name #type_g #display_where {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                f.write_str(#message)?;
+                let mut separator = ": ";
+                #(
+                    #(#try_cfg)*
+                    if let ::core::option::Option::Some(error) = &self.#try_ident {
+                        ::core::write!(f, "{}`{}`: {}", separator, #try_name, error)?;
+                        separator = ", ";
+                    }
+                )*
+                let _ = separator;
+                ::core::result::Result::Ok(())
+            }
+        }
+
+        #fancy_default::__impl_error! { [#impl_g] [#error_name #type_g] [#error_where] }
+    };
+
+    Ok((body, Some(error)))
+}
+
+/// The error type of a fallible field, `<T as FromStr>::Err` if not given.
+fn error_type(ty: &Type, error: Option<&Type>) -> TokenStream {
+    match error {
+        Some(error) => quote! { #error },
+        None => quote! { <#ty as ::core::str::FromStr>::Err },
+    }
+}
@@ -6,13 +6,17 @@ use syn::{
     LitBool, Meta, Token,
 };
 
-use crate::default::enum_impl;
+use crate::{
+    crate_path::CrateRoot,
+    default::{enum_impl, struct_impl::cfg_attrs},
+};
 
 pub(crate) fn process_variant_default(input: TokenStream) -> syn::Result<TokenStream> {
     let input: DeriveInput = parse2(input)?;
+    let root = CrateRoot::of(&input)?;
     match &input.data {
         syn::Data::Enum(enum_data) => {
-            impl_enum_variant(&input.attrs, enum_data, &input.ident, &input.generics)
+            impl_enum_variant(&input.attrs, enum_data, &input.ident, &input.generics, &root)
         }
         _ => unimplemented!(),
     }
@@ -97,6 +101,7 @@ pub(crate) fn impl_enum_variant(
     data: &DataEnum,
     name: &Ident,
     generics: &Generics,
+    root: &CrateRoot,
 ) -> syn::Result<TokenStream> {
     let meta_config = VariantConfig::parse_attr(
         if meta_attr.iter().any(|a| a.meta.path().is_ident("variant")) {
@@ -123,12 +128,14 @@ pub(crate) fn impl_enum_variant(
             continue;
         }
 
-        let default_block = enum_impl::impl_enum_variant(variant, name)?;
+        let default_block = enum_impl::impl_enum_variant(variant, name, root)?;
         let variant_name = variant.ident.to_string();
+        let gates = cfg_attrs(&variant.attrs);
 
         if cfg.function {
             let fn_name = format_ident!("default_{}", variant_name.to_snake());
             expanded.extend(quote! {
+                #(#gates)*
                 fn #fn_name() -> Self {
                     #default_block
                 }
@@ -138,6 +145,7 @@ pub(crate) fn impl_enum_variant(
         if cfg.constant {
             let const_name = format_ident!("{}", variant_name.to_snake().to_ascii_uppercase());
             expanded.extend(quote! {
+                #(#gates)*
                 const #const_name: Self = #default_block;
             })
         }
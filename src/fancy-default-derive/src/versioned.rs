@@ -0,0 +1,68 @@
+use itertools::Itertools;
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::{parse2, DeriveInput, Fields};
+
+use crate::crate_path::CrateRoot;
+use crate::default::struct_impl::FieldConfig;
+
+pub(crate) fn process_versioned_default(input: TokenStream) -> syn::Result<TokenStream> {
+    let input: DeriveInput = parse2(input)?;
+    impl_versioned_default(&input)
+}
+
+pub(crate) fn impl_versioned_default(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let syn::Data::Struct(struct_data) = &input.data else {
+        return Err(syn::Error::new(
+            Span::call_site(),
+            "`VersionedDefault` is only supported on structs",
+        ));
+    };
+
+    let root = CrateRoot::of(input)?;
+    let fancy_default = root.path()?;
+    let version = quote! { version };
+    let fields = struct_data
+        .fields
+        .iter()
+        .enumerate()
+        .map(|(idx, field)| {
+            let config = FieldConfig::parse_attr(&field.attrs, &root)?;
+            if config.required {
+                return Err(syn::Error::new_spanned(
+                    field,
+                    "`required` fields are not supported by `VersionedDefault`",
+                ));
+            }
+            let ident = field
+                .ident
+                .clone()
+                .unwrap_or_else(|| format_ident!("field_{}", idx));
+            let ty = &field.ty;
+            let value = config.versioned_value(ty, &version);
+            let cfg = config.cfg_attrs;
+            let value = quote! { #(#cfg)* let #ident: #ty = #value; };
+            Ok((ident, value, cfg))
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+    let (ident, value, cfg): (Vec<_>, Vec<_>, Vec<_>) = fields.into_iter().multiunzip();
+
+    let name = &input.ident;
+    let construct = match &struct_data.fields {
+        Fields::Named(_) => quote! { #name { #(#(#cfg)* #ident,)* } },
+        Fields::Unnamed(_) => quote! { #name(#(#(#cfg)* #ident),*) },
+        Fields::Unit => quote! { #name },
+    };
+    let (impl_g, type_g, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_g #fancy_default::traits::VersionedDefault for #name #type_g #where_clause {
+            fn default_for_version(#version: &#fancy_default::version::Version) -> Self {
+                let _ = #version;
+                #(#value)*
+                #construct
+            }
+        }
+    })
+}
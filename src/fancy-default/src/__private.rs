@@ -0,0 +1,168 @@
+//! Implementation details of the derive macros. Not public API.
+
+use core::future::Future;
+use core::marker::PhantomData;
+use core::mem;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+
+use crate::traits::{Default, DefaultWith, FieldDefault};
+
+#[cfg(feature = "alloc")]
+use crate::traits::FieldDiff;
+#[cfg(feature = "alloc")]
+pub use alloc::vec::Vec;
+#[cfg(feature = "alloc")]
+use alloc::{boxed::Box, format, string::ToString};
+#[cfg(feature = "alloc")]
+use core::fmt::Debug;
+#[cfg(feature = "serde")]
+pub use serde;
+#[cfg(feature = "schemars")]
+pub use serde_json;
+
+/// Selects `DefaultWith<Ctx>` for `T` if it is implemented, and falls back to `Default` otherwise.
+///
+/// The generated code calls `(&DefaultWithProbe::<T, Ctx>::new()).default_with(ctx)`,
+/// where method resolution prefers [`ViaDefaultWith`], implemented on the probe itself,
+/// over [`ViaDefault`], implemented on a reference to it.
+pub struct DefaultWithProbe<T, Ctx: ?Sized>(PhantomData<fn(&Ctx) -> T>);
+
+#[allow(clippy::new_without_default)]
+impl<T, Ctx: ?Sized> DefaultWithProbe<T, Ctx> {
+    pub fn new() -> Self {
+        DefaultWithProbe(PhantomData)
+    }
+}
+
+pub trait ViaDefaultWith<T, Ctx: ?Sized> {
+    fn default_with(&self, ctx: &Ctx) -> T;
+}
+
+impl<T: DefaultWith<Ctx>, Ctx: ?Sized> ViaDefaultWith<T, Ctx> for DefaultWithProbe<T, Ctx> {
+    fn default_with(&self, ctx: &Ctx) -> T {
+        T::default_with(ctx)
+    }
+}
+
+pub trait ViaDefault<T, Ctx: ?Sized> {
+    fn default_with(&self, ctx: &Ctx) -> T;
+}
+
+impl<T: Default, Ctx: ?Sized> ViaDefault<T, Ctx> for &DefaultWithProbe<T, Ctx> {
+    fn default_with(&self, _ctx: &Ctx) -> T {
+        T::default()
+    }
+}
+
+/// Call a `ctx` closure, so that its parameter type is inferred from `Ctx`.
+pub fn call_with<Ctx: ?Sized, T>(f: impl FnOnce(&Ctx) -> T, ctx: &Ctx) -> T {
+    f(ctx)
+}
+
+/// Construct a [`FieldDefault`], which is `#[non_exhaustive]`.
+pub const fn field_default(
+    name: &'static str,
+    ty: &'static str,
+    expr: &'static str,
+    required: bool,
+    doc: &'static str,
+) -> FieldDefault {
+    FieldDefault {
+        name,
+        ty,
+        expr,
+        required,
+        doc,
+    }
+}
+
+/// A future in a join, which keeps its output once completed.
+pub enum MaybeDone<F: Future> {
+    Pending(F),
+    Done(F::Output),
+    Taken,
+}
+
+impl<F: Future> MaybeDone<F> {
+    /// Poll the future if it is not completed yet, returning whether it is completed.
+    pub fn poll_done(self: Pin<&mut Self>, cx: &mut Context<'_>) -> bool {
+        // SAFETY: the future is never moved out of `Pending`,
+        // it is only dropped in place once completed.
+        let this = unsafe { self.get_unchecked_mut() };
+        if let MaybeDone::Pending(future) = this {
+            match unsafe { Pin::new_unchecked(future) }.poll(cx) {
+                Poll::Ready(output) => *this = MaybeDone::Done(output),
+                Poll::Pending => return false,
+            }
+        }
+        true
+    }
+
+    /// Take the output of the completed future.
+    pub fn take(self: Pin<&mut Self>) -> F::Output {
+        // SAFETY: only the output is moved out, which is never pinned.
+        let this = unsafe { self.get_unchecked_mut() };
+        if !matches!(this, MaybeDone::Done(_)) {
+            panic!("unexpected internal error: the future is not completed");
+        }
+        match mem::replace(this, MaybeDone::Taken) {
+            MaybeDone::Done(output) => output,
+            _ => unreachable!(),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+pub fn field_diff<'a, T: Debug + 'a>(path: &str, value: &'a T, default: T) -> FieldDiff<'a> {
+    FieldDiff {
+        path: path.to_string(),
+        value,
+        default: Box::new(default),
+    }
+}
+
+/// Prefix the path of a diff from a nested field.
+#[cfg(feature = "alloc")]
+pub fn nested_diff<'a>(path: &str, diff: FieldDiff<'a>) -> FieldDiff<'a> {
+    FieldDiff {
+        path: format!("{}.{}", path, diff.path),
+        ..diff
+    }
+}
+
+/// Parse the value of an override.
+#[cfg(feature = "alloc")]
+pub fn parse_override<T>(key: &str, value: &str) -> Result<T, crate::traits::OverrideError>
+where
+    T: core::str::FromStr,
+    T::Err: core::fmt::Display,
+{
+    value
+        .parse()
+        .map_err(|error: T::Err| crate::traits::OverrideError::InvalidValue {
+            key: key.to_string(),
+            message: error.to_string(),
+        })
+}
+
+#[cfg(feature = "std")]
+pub use std::error::Error;
+
+/// Implement `Error` for the error type of `TryDefault`, if the `std` feature is enabled.
+#[cfg(feature = "std")]
+#[macro_export]
+#[doc(hidden)]
+macro_rules! __impl_error {
+    ([$($impl_g:tt)*] [$($ty:tt)*] [$($where_clause:tt)*]) => {
+        impl $($impl_g)* $crate::__private::Error for $($ty)* $($where_clause)* {}
+    };
+}
+
+/// Implement `Error` for the error type of `TryDefault`, if the `std` feature is enabled.
+#[cfg(not(feature = "std"))]
+#[macro_export]
+#[doc(hidden)]
+macro_rules! __impl_error {
+    ($($tokens:tt)*) => {};
+}
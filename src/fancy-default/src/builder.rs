@@ -0,0 +1,7 @@
+/// Typestate of a required field that has not been set yet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Unset;
+
+/// Typestate of a required field that has been set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Set<T>(pub T);
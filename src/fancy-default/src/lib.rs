@@ -12,6 +12,21 @@
 //! - [the `Default` macro](#fancy_defaultderivedefault)
 //! - [the `ConstDefault` macro](#fancy_defaultderiveconstdefault)
 //! - [the `VariantDefault` macro](#fancy_defaultderivevariantdefault)
+//! - [the `DefaultSchema` macro](#fancy_defaultderivedefaultschema)
+//! - [the `DefaultBuilder` macro](#fancy_defaultderivedefaultbuilder)
+//! - [the `TryDefault` macro](#fancy_defaultderivetrydefault)
+//! - [the `DefaultWith` macro](#fancy_defaultderivedefaultwith)
+//! - [the `AsyncDefault` macro](#fancy_defaultderiveasyncdefault)
+//! - [the `DefaultPartial` macro](#fancy_defaultderivedefaultpartial)
+//! - [the `MergeDefaults` macro](#fancy_defaultderivemergedefaults)
+//! - [the `DiffDefault` and `DebugNonDefault` macros](#fancy_defaultderivediffdefault)
+//! - [the `Deserialize` macro](#fancy_defaultderivedeserialize)
+//! - [the `Serialize` macro](#fancy_defaultderiveserialize)
+//! - [the `JsonSchemaDefaults` macro](#fancy_defaultderivejsonschemadefaults)
+//! - [the `ApplyOverrides` macro](#fancy_defaultderiveapplyoverrides)
+//! - [the `VersionedDefault` macro](#fancy_defaultderiveversioneddefault)
+//! - [the `defaults` attribute macro](#fancy_defaultderivedefaults)
+//! - [the `structure` macro](#fancy_defaultderivestructure)
 //! - [License & MSRV](#msrv)
 //! 
 //! ## Generic default value configuration
@@ -29,9 +44,187 @@
 //!     so you can write expressions with default values directly,
 //!     like: `#[default = "foobar".to_owned()]`.
 //!   - `#[default(expr = <expr>)]`: Same meaning as the previous format.
+//!   - `#[default(provider = <type>)]`: Use `<type>` as a `DefaultProvider`
+//!     of the field's type, like: `#[default(provider = RegistryPort)]`.
+//! 
+//!     Providers are reusable across types and crates, and can be generic parameters,
+//!     through the `WithDefault<T, P>` wrapper,
+//!     whose `Default` impl calls `P::provide()`.
+//!   - Fields of type `Setting<T>` remember whether they were explicitly set:
+//!     their default values are wrapped in `Setting::Default`, like `Setting::Default(8080)`
+//!     for `#[default = 8080] port: Setting<u16>`,
+//!     while assigned, parsed or deserialized values are `Setting::Explicit`.
+//!     `derive(Default)` and `derive(ConstDefault)` then also generate `fn explicit_fields(&self)`,
+//!     iterating over the names of the explicitly set `Setting` fields.
+//! 
+//!     **Note**: The type is recognized by its name, so it must be written as `Setting<...>`.
+//! 
+//!     ```rust
+//!     use fancy_default::{Default, Setting};
+//! 
+//!     #[derive(Default)]
+//!     struct Server {
+//!         #[default = 8080]
+//!         port: Setting<u16>,
+//!         workers: Setting<usize>,
+//!     }
+//! 
+//!     let mut server = Server::default();
+//!     assert_eq!(server.port, Setting::Default(8080));
+//!     server.workers.set(4);
+//!     assert_eq!(server.explicit_fields().collect::<Vec<_>>(), ["workers"]);
+//!     ```
+//!   - `#[default(nested(<path> = <expr>, ...))]`: Start from the field's default value
+//!     and override some of its sub-fields, possibly through dotted paths,
+//!     like: `#[default(nested(max_retries = 5, backoff.factor = 2.0))]`.
+//! 
+//!     The overrides are applied on top of `expr` if it is given,
+//!     or `core::default::Default::default()` otherwise.
+//!     Paths that do not exist on the field's type are reported at compile time.
+//!   - `#[default(cfg(<predicate>) = <expr>, ...)]`: Use the value of the first `cfg` predicate
+//!     that holds, like: `#[default(cfg(debug_assertions) = 1, cfg(not(debug_assertions)) = 64)]`.
+//!     The explicit default value, if given, is used when none of them holds,
+//!     otherwise such configurations are reported at compile time.
+//!   - `#[default(required)]`: The field has no default value (struct only).
+//!     A struct with required fields does not implement `Default`/`ConstDefault`.
+//!     Instead, `derive(Default)` generates a `new` constructor (a `const fn` for `derive(ConstDefault)`),
+//!     whose parameters are the required fields in declaration order,
+//!     and all other fields are filled with their default values.
+//!     To derive both on such structs, add `#[default(const)]`, so that only `Default` generates `new`, as a `const fn`.
+//! 
+//!     ```rust
+//!     use fancy_default::Default;
+//! 
+//!     #[derive(Debug, Default, PartialEq, Eq)]
+//!     struct Client {
+//!         #[default(required)]
+//!         endpoint: String,
+//!         #[default = 3]
+//!         retries: usize,
+//!     }
+//! 
+//!     assert_eq!(
+//!         Client::new("localhost".to_owned()),
+//!         Client { endpoint: "localhost".to_owned(), retries: 3 },
+//!     );
+//!     ```
+//! - Struct configuration(struct only):
+//!   - `#[default(const)]`: The struct derives both `Default` and `ConstDefault`.
+//!     The `new` constructor of `required` fields, the `make` macro, the `with` setters
+//!     and `explicit_fields` are then only generated by `Default`, rather than by both derive macros,
+//!     and `new` is a `const fn`. `ConstDefault` alone does not generate them with this flag.
+//!     `#[defaults]` adds this automatically when both are listed.
+//! 
+//!     ```rust
+//!     use fancy_default::{ConstDefault, Default};
+//! 
+//!     #[derive(Debug, Default, ConstDefault, PartialEq, Eq)]
+//!     #[default(const)]
+//!     struct Client {
+//!         #[default(required)]
+//!         endpoint: &'static str,
+//!         #[default = 3]
+//!         retries: usize,
+//!     }
+//! 
+//!     const CLIENT: Client = Client::new("localhost");
+//!     assert_eq!(CLIENT, Client { endpoint: "localhost", retries: 3 });
+//!     ```
+//!   - `#[default(make = <name>)]`: Generate a `<name>!` macro alongside the `Default`
+//!     or `ConstDefault` impl,
+//!     which builds the struct from a struct literal with some fields left out,
+//!     like: `config! { port: 1 }`.
+//!     Only the default values of the missing fields are evaluated,
+//!     so unlike `Config { port: 1, ..Default::default() }`,
+//!     it also works in `const` contexts when the default values are constant.
+//! 
+//!     **Note**: The macro is declared with `macro_rules!` in the module of the struct,
+//!     so it can be imported with `use` within the crate.
+//!     It refers to the struct and the names used by the default values as written,
+//!     so they must be in scope wherever the macro is invoked.
+//! 
+//!     ```rust
+//!     mod config {
+//!         #[derive(fancy_default::ConstDefault)]
+//!         #[default(make = config)]
+//!         pub struct Config {
+//!             #[default = 8080]
+//!             pub port: u16,
+//!             #[default = "localhost"]
+//!             pub host: &'static str,
+//!         }
+//!     }
+//! 
+//!     use config::{config, Config};
+//! 
+//!     const CONFIG: Config = config! { port: 9000 };
+//!     assert_eq!((CONFIG.port, CONFIG.host), (9000, "localhost"));
+//!     ```
+//!   - `#[default(with)]`/`#[default(with(<config>))]`: Generate a chainable
+//!     `fn with_<field>(self, value: impl Into<T>) -> Self` setter for every field,
+//!     alongside the `Default` or `ConstDefault` impl.
+//!     - `vis = "<visibility>"`: The visibility of the setters.  
+//!       Default: the visibility of the struct.
+//!     - `into`/`into = <bool>`: Whether the setters take `impl Into<T>` rather than `T`.  
+//!       Default: `true`.
+//!     - `const`/`const = <bool>`: Generate `const fn` setters taking `T` instead,
+//!       like: `Config::DEFAULT.with_port(9000)`.  
+//!       Default: `false`.
+//! 
+//!       **Note**: A `const fn` cannot drop the value it replaces,
+//!       so this fails to compile (E0493) if any field type needs to be dropped, like `String`:
+//! 
+//!       ```rust,compile_fail,E0493
+//!       #[derive(fancy_default::Default)]
+//!       #[default(with(const))]
+//!       struct Config {
+//!           name: String,
+//!       }
+//!       ```
+//! 
+//!     ```rust
+//!     use fancy_default::ConstDefault;
+//! 
+//!     #[derive(Debug, ConstDefault, PartialEq, Eq)]
+//!     #[default(with(const))]
+//!     struct Config {
+//!         #[default = 8080]
+//!         port: u16,
+//!         #[default = "localhost"]
+//!         host: &'static str,
+//!     }
+//! 
+//!     const CONFIG: Config = Config::DEFAULT.with_port(9000);
+//!     assert_eq!(CONFIG, Config { port: 9000, host: "localhost" });
+//!     ```
 //! - Variant configuration(enum only):
 //!   - `#[default]`: Set the variant as the default variant of the enum.  
 //!     This attribute works the same as the standard library's `#[default]`.
+//!   - `#[default(cfg(<predicate>))]`: Set the variant as the default variant
+//!     if `<predicate>` holds, where the first variant matching wins,
+//!     like: `#[default(cfg(debug_assertions))]`.
+//!     Configurations where no variant matches are reported at compile time.
+//! - Enum configuration(enum only):
+//!   - `#[default(variant = <variant>)]`: Set `<variant>` as the default variant of the enum,
+//!     instead of marking the variant itself.
+//!     This can also be selected conditionally through `cfg_attr`,
+//!     like: `#[cfg_attr(feature = "foo", default(variant = Foo))]`.
+//!   - `#[default(variant = <variant>, expr = <expr>)]`: Use `<expr>` as the default value,
+//!     overriding the payload of the selected variant,
+//!     like: `#[default(variant = Tuple, expr = Enum::Tuple(3))]`.
+//!     `<expr>` must construct the selected variant, like `Enum::Tuple(...)`, `Self::Tuple { .. }`,
+//!     or a constant or function call returning it. Constructing another variant directly is rejected.
+//! - Crate configuration(struct and enum):
+//!   - `#[default(crate = <path>)]`: Refer to this library through `<path>` in the generated code,
+//!     instead of `::fancy_default`, like: `#[default(crate = my_prelude::fancy_default)]`.
+//!     This is required when the library is renamed in `Cargo.toml`,
+//!     or re-exported from another crate.
+//!     Inside `macro_rules!`, use `$crate` to refer to the re-export, like: `#[default(crate = $crate::fancy_default)]`.
+//!     The path may also be given as a string, like: `#[default(crate = "my_prelude::fancy_default")]`.
+//!   - `#[default(core)]`: Only refer to `core` in the generated code,
+//!     so that `derive(Default)` implements `::core::default::Default`
+//!     without this library being a direct dependency.
+//!     Anything requiring this library, like `ConstDefault` or `provider`, is reported at compile time.
 //! 
 //! ## `fancy_default::derive::Default`
 //! 
@@ -97,13 +290,13 @@
 //! 
 //! - `#[variant(<config>)]`:
 //!   - `const`/`const = <bool>`: Whether to generate constant default values.
-//!   The corresponding constant name is the UPPER_CASE version of the current enumeration.  
-//!   Default: `false`.
-//!   Alias: `constant`.
+//!     The corresponding constant name is the UPPER_CASE version of the current enumeration.  
+//!     Default: `false`.
+//!     Alias: `constant`.
 //!   - `func`/`func = <bool>`: Whether to generate static methods that return default values.
-//!   The corresponding constant name is the snake_case version of the current enumeration and has a `default_` prefix.  
-//!   Default: `true`.
-//!   Alias: `fn`, `function`.
+//!     The corresponding constant name is the snake_case version of the current enumeration and has a `default_` prefix.  
+//!     Default: `true`.
+//!     Alias: `fn`, `function`.
 //! 
 //! **Note:** This attribute can be added to an enum body or to a single variant.
 //! If added to the enum body, it will override the default generated configuration.
@@ -135,10 +328,609 @@
 //! );
 //! ```
 //! 
+//! ## `fancy_default::derive::DefaultSchema`
+//! 
+//! Expose the default value configuration of each field at runtime,
+//! for example to generate documentation or configuration UIs.
+//! Each `FieldDefault` holds the field name,
+//! its type, its default value expression and its doc comments, all as `&'static str`.
+//! For enums, the fields of the default variant are listed,
+//! and the name of the default variant is available through `default_variant`.
+//! 
+//! **Basic Usage:**
+//! 
+//! ```rust
+//! use fancy_default::DefaultSchema;
+//! 
+//! #[derive(DefaultSchema)]
+//! struct Config {
+//!     /// The port to listen on.
+//!     #[default = 8080]
+//!     port: u16,
+//! }
+//! 
+//! let schema = Config::default_schema();
+//! assert_eq!(schema[0].name, "port");
+//! assert_eq!(schema[0].ty, "u16");
+//! assert_eq!(schema[0].expr, "8080");
+//! assert_eq!(schema[0].doc, "The port to listen on.");
+//! ```
+//! 
+//! ## `fancy_default::derive::DefaultBuilder`
+//! 
+//! Generate a builder, whose setters are named after the fields
+//! (`field_<index>` for tuple fields),
+//! and whose unset fields fall back to their default values.
+//! 
+//! - For structs, `Struct::builder()` returns a `StructBuilder`.
+//! - For enums, `Enum::<variant>_builder()` returns an `Enum<Variant>Builder` for each variant.
+//! 
+//! Fields marked `#[default(required)]` are tracked with typestate generic parameters
+//! (see [`builder::Unset`] and [`builder::Set`]),
+//! so `build()` only exists once all of them are set, and each of them can only be set once.
+//! 
+//! **Basic Usage:**
+//! 
+//! ```rust
+//! use fancy_default::DefaultBuilder;
+//! 
+//! #[derive(Debug, DefaultBuilder, PartialEq, Eq)]
+//! struct Client {
+//!     #[default(required)]
+//!     endpoint: String,
+//!     #[default = 3]
+//!     retries: usize,
+//!     #[default = 30]
+//!     timeout: u64,
+//! }
+//! 
+//! let client = Client::builder()
+//!     .timeout(10)
+//!     .endpoint("localhost".to_owned())
+//!     .build();
+//! assert_eq!(
+//!     client,
+//!     Client {
+//!         endpoint: "localhost".to_owned(),
+//!         retries: 3,
+//!         timeout: 10,
+//!     }
+//! );
+//! ```
+//! 
+//! ```rust,compile_fail
+//! # use fancy_default::DefaultBuilder;
+//! # #[derive(DefaultBuilder)]
+//! # struct Client {
+//! #     #[default(required)]
+//! #     endpoint: String,
+//! # }
+//! // `endpoint` is required
+//! let client = Client::builder().build();
+//! ```
+//! 
+//! ## `fancy_default::derive::TryDefault`
+//! 
+//! Derive `TryDefault`, a fallible version of `Default`,
+//! for default values that come from parsing or other fallible operations.
+//! 
+//! **Config Syntax:**
+//! 
+//! - `#[default(try = <expr>)]`: Use the `Ok` value of `<expr>` as the default value.
+//!   Other fields use the same configuration as `Default`.
+//! - `#[default(try = <expr>, error = <type>)]`: The error type of `<expr>`.  
+//!   Default: `<T as FromStr>::Err`, where `T` is the type of the field.
+//! 
+//! All fallible fields are evaluated, and their errors are aggregated into
+//! a generated `<Type>DefaultError` struct, which has an `Option` field named after each
+//! fallible field, and implements `Debug`, `Display` and, with the `std` feature, `Error`.
+//! The error struct has the same generic parameters as the type.
+//! If no field is fallible, the error type is `core::convert::Infallible`.
+//! 
+//! **Basic Usage:**
+//! 
+//! ```rust
+//! use fancy_default::TryDefault;
+//! use std::net::SocketAddr;
+//! 
+//! #[derive(Debug, TryDefault)]
+//! struct Config {
+//!     #[default(try = "127.0.0.1:80".parse())]
+//!     addr: SocketAddr,
+//!     #[default(try = "not a number".parse())]
+//!     port: u16,
+//!     #[default = 3]
+//!     retries: usize,
+//! }
+//! 
+//! let error = Config::try_default().unwrap_err();
+//! assert!(error.addr.is_none());
+//! assert!(error.port.is_some());
+//! assert_eq!(
+//!     error.to_string(),
+//!     "failed to create the default value of `Config`: `port`: invalid digit found in string",
+//! );
+//! ```
+//! 
+//! ## `fancy_default::derive::DefaultWith`
+//! 
+//! Derive `DefaultWith<Ctx>`, whose default value is created from
+//! a context, such as the paths or settings of the application.
+//! 
+//! **Config Syntax:**
+//! 
+//! - `#[default(ctx = <closure>)]` on fields: Call `<closure>` with `&Ctx` to get the default value.
+//!   Other fields use the same configuration as `Default`.
+//! - `#[default(ctx = <type>)]` on the struct or enum: The context type.  
+//!   Default: inferred from the first closure whose parameter is annotated, like `|c: &Ctx|`.
+//! 
+//! Fields without an explicit default value use `DefaultWith<Ctx>` if their type implements it,
+//! so nested types receive the same context, and fall back to `Default` otherwise.
+//! 
+//! **Basic Usage:**
+//! 
+//! ```rust
+//! use fancy_default::DefaultWith;
+//! use std::path::PathBuf;
+//! 
+//! struct AppCtx {
+//!     data_dir: PathBuf,
+//! }
+//! 
+//! #[derive(Debug, DefaultWith, PartialEq, Eq)]
+//! struct Cache {
+//!     #[default(ctx = |c: &AppCtx| c.data_dir.join("cache"))]
+//!     dir: PathBuf,
+//!     #[default = 64]
+//!     capacity: usize,
+//! }
+//! 
+//! #[derive(Debug, DefaultWith, PartialEq, Eq)]
+//! #[default(ctx = AppCtx)]
+//! struct Config {
+//!     cache: Cache,
+//!     name: String,
+//! }
+//! 
+//! let ctx = AppCtx { data_dir: PathBuf::from("/var/app") };
+//! let config = Config::default_with(&ctx);
+//! assert_eq!(config.cache.dir, PathBuf::from("/var/app/cache"));
+//! assert_eq!(config.cache.capacity, 64);
+//! assert_eq!(config.name, "");
+//! ```
+//! 
+//! ## `fancy_default::derive::AsyncDefault`
+//! 
+//! Derive `AsyncDefault`, an asynchronous version of `Default`,
+//! which does not depend on any executor.
+//! 
+//! **Config Syntax:**
+//! 
+//! - `#[default(async = <expr>.await)]`: Use the awaited value as the default value.
+//!   Other fields use the same configuration as `Default`.
+//! 
+//! Synchronous fields are evaluated first, and then all asynchronous fields
+//! are awaited concurrently, like a `join` of their futures.
+//! 
+//! **Basic Usage:**
+//! 
+//! ```rust
+//! use fancy_default::AsyncDefault;
+//! 
+//! async fn connect(addr: &str) -> String {
+//!     format!("connected to {}", addr)
+//! }
+//! 
+//! #[derive(Debug, AsyncDefault)]
+//! struct Service {
+//!     #[default(async = connect("/run/app.sock").await)]
+//!     pool: String,
+//!     #[default = 8]
+//!     workers: usize,
+//! }
+//! 
+//! // any executor works, this one just polls the future until it completes
+//! fn block_on<F: core::future::Future>(future: F) -> F::Output {
+//!     let mut future = core::pin::pin!(future);
+//!     let mut cx = core::task::Context::from_waker(core::task::Waker::noop());
+//!     loop {
+//!         if let core::task::Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+//!             return output;
+//!         }
+//!     }
+//! }
+//! 
+//! let service = block_on(Service::default_async());
+//! assert_eq!(service.pool, "connected to /run/app.sock");
+//! assert_eq!(service.workers, 8);
+//! ```
+//! 
+//! ## `fancy_default::derive::DefaultPartial`
+//! 
+//! Derive `DefaultPartial` for layered configuration,
+//! by generating a `<Struct>Partial` struct with every field wrapped in `Option`.
+//! 
+//! The partial struct implements `Default` (with nothing set) and `Partial`:
+//! 
+//! - `merge(self, other)`: Merge two layers, where the fields set in `other` take precedence.
+//! - `resolve(self)`: Build the struct, where the fields that are not set use their default values.
+//! - `apply(self, base)`: Override the fields of `base` that are set.
+//! 
+//! **Config Syntax:**
+//! 
+//! - `#[default(nested)]` on fields: The field type also derives `DefaultPartial`,
+//!   and the partial field is its partial type rather than an `Option`, so that layers are merged recursively.
+//!   Unless a default value is given, its default is resolved from an empty layer of the field type.
+//! - `#[default(partial(derive(<derive>, ...)))]` on the struct: Derive macros for the partial struct,
+//!   like: `#[default(partial(derive(Debug, Clone)))]`.
+//! 
+//! **Basic Usage:**
+//! 
+//! ```rust
+//! use fancy_default::{DefaultPartial, Partial};
+//! 
+//! #[derive(Debug, DefaultPartial)]
+//! struct Server {
+//!     #[default = 8080]
+//!     port: u16,
+//!     #[default(expr = "localhost".to_owned())]
+//!     host: String,
+//! }
+//! 
+//! #[derive(Debug, DefaultPartial)]
+//! struct Config {
+//!     #[default(nested)]
+//!     server: Server,
+//!     #[default = 4]
+//!     workers: usize,
+//! }
+//! 
+//! let file = ConfigPartial {
+//!     server: ServerPartial { port: Some(80), host: None },
+//!     workers: Some(8),
+//! };
+//! let env = ConfigPartial {
+//!     workers: Some(16),
+//!     ..Default::default()
+//! };
+//! 
+//! let config = file.merge(env).resolve();
+//! assert_eq!(config.server.port, 80);
+//! assert_eq!(config.server.host, "localhost");
+//! assert_eq!(config.workers, 16);
+//! ```
+//! 
+//! ## `fancy_default::derive::MergeDefaults`
+//! 
+//! Derive `MergeDefaults`, which layers two values of the same type
+//! without a separate partial type: `merge_non_default(&mut self, overlay)` copies each field
+//! of `overlay` only if it differs from the field's default value.
+//! 
+//! Fields are compared with `PartialEq` against their default values, which are configured
+//! with the same syntax as `Default`.
+//! Fields marked `#[default(nested)]` are merged recursively instead,
+//! against the default values of the field type,
+//! with the overrides of `#[default(nested(...))]` applied.
+//! 
+//! **Basic Usage:**
+//! 
+//! ```rust
+//! use fancy_default::{Default, MergeDefaults};
+//! 
+//! #[derive(Debug, Default, MergeDefaults)]
+//! struct Config {
+//!     #[default = 8080]
+//!     port: u16,
+//!     #[default = 4]
+//!     workers: usize,
+//! }
+//! 
+//! let mut config = Config { port: 80, workers: 8 };
+//! config.merge_non_default(Config { port: 443, workers: 4 });
+//! assert_eq!(config.port, 443);
+//! assert_eq!(config.workers, 8);
+//! ```
+//! 
+//! ## `fancy_default::derive::DiffDefault`
+//! 
+//! Derive `DiffDefault`, which compares a value against its default values,
+//! like for "show effective config" commands.
+//! `non_default_fields(&self)` yields a `FieldDiff` for each field
+//! that differs from its default value, holding the path of the field, like `server.port`,
+//! and `Debug` views of its current and default values.
+//! 
+//! `DiffDefault` requires the `alloc` feature.
+//! 
+//! `derive(DebugNonDefault)` implements `Debug` listing only the fields
+//! that differ from their default values, without allocating.
+//! 
+//! Both compare fields with `PartialEq`, against the default values configured with the same syntax as `Default`.
+//! `DiffDefault` compares fields marked `#[default(nested)]` recursively instead, and yields their differing sub-fields,
+//! against the overridden default values given with `nested(...)`.
+//! `DebugNonDefault` lists nested fields as a whole when they differ from their default values,
+//! relying on the `Debug` implementation of the field type.
+//! Tuple structs are printed like `Pair(.., 3)`, keeping the positions of omitted fields.
+//! 
+//! **Basic Usage:**
+//! 
+//! ```rust
+//! use fancy_default::DebugNonDefault;
+//! 
+//! #[derive(DebugNonDefault, PartialEq)]
+//! struct Config {
+//!     #[default = 8080]
+//!     port: u16,
+//!     #[default = 4]
+//!     workers: usize,
+//! }
+//! 
+//! let config = Config { port: 443, workers: 4 };
+//! assert_eq!(format!("{:?}", config), "Config { port: 443, .. }");
+//! ```
+//! 
+//! ## `fancy_default::derive::Deserialize`
+//! 
+//! Derive `serde::Deserialize` for structs with named fields,
+//! where any missing field falls back to its configured default value,
+//! so that the defaults are not repeated in `#[serde(default = "...")]` attributes.
+//! Fields marked `#[default(required)]` are reported as missing instead.
+//! 
+//! This requires the `serde` feature.
+//! 
+//! **Config Syntax:**
+//! 
+//! - `#[default(rename = "<name>")]` on fields: The serialized name of the field.
+//! - `#[default(rename_all = "<rule>")]` on the struct: The case convention of the serialized names,
+//!   one of `lowercase`, `UPPERCASE`, `PascalCase`, `camelCase`, `snake_case`,
+//!   `SCREAMING_SNAKE_CASE`, `kebab-case` and `SCREAMING-KEBAB-CASE`.
+//! - `#[default(deny_unknown_fields)]` on the struct: Reject unknown fields, which are ignored by default.
+//! 
+//! **Basic Usage:**
+//! 
+//! ```rust
+//! # #[cfg(feature = "serde")] {
+//! #[derive(Debug, fancy_default::Deserialize)]
+//! #[default(rename_all = "kebab-case", deny_unknown_fields)]
+//! struct Config {
+//!     #[default = 8080]
+//!     listen_port: u16,
+//!     #[default(expr = "localhost".to_owned())]
+//!     host: String,
+//! }
+//! 
+//! let config: Config = serde_json::from_str(r#"{ "listen-port": 80 }"#).unwrap();
+//! assert_eq!(config.listen_port, 80);
+//! assert_eq!(config.host, "localhost");
+//! # }
+//! ```
+//! 
+//! ## `fancy_default::derive::Serialize`
+//! 
+//! Derive `serde::Serialize` for structs with named fields, omitting the fields equal to
+//! their configured default values, so that serialized configurations only contain what was changed.
+//! The fields are compared with `PartialEq`, and `#[default(required)]` fields are always included.
+//! 
+//! This requires the `serde` feature, and accepts the same `rename` and `rename_all` options as
+//! [`Deserialize`](#fancy_defaultderivedeserialize), so that the output can be read back.
+//! 
+//! **Config Syntax:**
+//! 
+//! - `#[default(skip_defaults = <bool>)]` on the struct: Whether to omit the fields equal to their default values,
+//!   or always include all fields.  
+//!   Default: `true`.
+//! 
+//! **Basic Usage:**
+//! 
+//! ```rust
+//! # #[cfg(feature = "serde")] {
+//! #[derive(fancy_default::Serialize)]
+//! struct Config {
+//!     #[default = 8080]
+//!     port: u16,
+//!     #[default(expr = "localhost".to_owned())]
+//!     host: String,
+//! }
+//! 
+//! let config = Config { port: 80, host: "localhost".to_owned() };
+//! assert_eq!(serde_json::to_string(&config).unwrap(), r#"{"port":80}"#);
+//! # }
+//! ```
+//! 
+//! ## `fancy_default::derive::JsonSchemaDefaults`
+//! 
+//! Derive `JsonSchemaDefaults`, which serializes the configured
+//! default values with `serde_json`, to publish them in the JSON schemas generated by `schemars`.
+//! The schema is patched by `add_defaults`, used as a `transform` of `schemars`:
+//! 
+//! - For structs with named fields, each property with a default value gets its `default` keyword,
+//!   and is no longer `required`. Properties are named with the same `rename` and `rename_all` options as
+//!   [`Deserialize`](#fancy_defaultderivedeserialize).
+//! - For enums, the schema of the enum gets the default variant as its `default` keyword.
+//! 
+//! This requires the `schemars` feature, and the default values to implement `serde::Serialize`.
+//! 
+//! **Basic Usage:**
+//! 
+//! ```rust
+//! # #[cfg(feature = "schemars")] {
+//! use schemars::JsonSchema;
+//! 
+//! #[derive(fancy_default::JsonSchemaDefaults, JsonSchema)]
+//! #[schemars(transform = fancy_default::schemars::add_defaults::<Self>)]
+//! struct Config {
+//!     #[default = 8080]
+//!     port: u16,
+//! }
+//! 
+//! let schema = schemars::schema_for!(Config);
+//! assert_eq!(schema.pointer("/properties/port/default"), Some(&8080.into()));
+//! # }
+//! ```
+//! 
+//! ## `fancy_default::derive::ApplyOverrides`
+//! 
+//! Derive `ApplyOverrides`, a key/value override layer without serde:
+//! `apply(&mut self, key, value)` parses `value` with `FromStr` into the field at `key`,
+//! where fields marked `#[default(nested)]` are reached through dotted paths, like `server.tls.port`.
+//! Failures are reported as `OverrideError`, holding the full path.
+//! 
+//! The trait also provides helpers feeding it from:
+//! 
+//! - `apply_env(prefix, vars)`: environment variables, where `__` separates the path segments,
+//!   like `APP_SERVER__PORT` for `server.port` with the prefix `APP_`;
+//! - `apply_args(args)`: command line `-o key=value` pairs.
+//! 
+//! This requires the `alloc` feature, and `OverrideError` implements `Error` with the `std` feature.
+//! 
+//! **Basic Usage:**
+//! 
+//! ```rust
+//! # #[cfg(feature = "std")] {
+//! use fancy_default::{ApplyOverrides, Default};
+//! 
+//! #[derive(Default, ApplyOverrides)]
+//! struct Server {
+//!     #[default = 8080]
+//!     port: u16,
+//! }
+//! 
+//! #[derive(Default, ApplyOverrides)]
+//! struct Config {
+//!     #[default(nested)]
+//!     server: Server,
+//!     #[default = 4]
+//!     workers: usize,
+//! }
+//! 
+//! let mut config = Config::default();
+//! // usually `std::env::vars()` and `std::env::args().skip(1)`
+//! config.apply_env("APP_", [("APP_WORKERS", "8")])?;
+//! config.apply_args(["-o", "server.port=80"])?;
+//! config.apply("server.port", "443")?;
+//! assert_eq!(config.server.port, 443);
+//! assert_eq!(config.workers, 8);
+//! # }
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//! 
+//! ## `fancy_default::derive::VersionedDefault`
+//! 
+//! Derive `VersionedDefault`, whose `default_for_version(&Version)`
+//! creates the default value as it was in an older `Version`,
+//! so that persisted values keep their behavior when a default value changes.
+//! The other macros, like `Default`, use the newest default values.
+//! 
+//! Config Syntax:
+//! 
+//! - Field configuration:
+//!   - `#[default(expr = <expr>, since = "<version>", before = <expr>)]`: The default value is `expr`
+//!     since `version`, and `before` in the older versions.
+//!   - `#[default(versions("<version>" = <expr>, ...))]`: The default value of each version,
+//!     which applies until the next newer one.
+//!     The oldest value also applies to the older versions, unless `before = <expr>` is given.
+//!   - `#[default(nested)]`: Use the `VersionedDefault` of the field's type,
+//!     if no default value is given.
+//! 
+//! Versions are written as `major`, `major.minor` or `major.minor.patch`,
+//! and are checked at compile time.
+//! 
+//! **Basic Usage:**
+//! 
+//! ```rust
+//! use fancy_default::{Default, Version, VersionedDefault};
+//! 
+//! #[derive(Debug, Default, VersionedDefault, PartialEq)]
+//! struct Cache {
+//!     #[default(expr = 16, since = "2.0", before = 8)]
+//!     size: usize,
+//!     #[default(versions("1.0" = 0.5, "1.4" = 0.75, "3.0" = 0.9))]
+//!     ratio: f64,
+//! }
+//! 
+//! assert_eq!(Cache::default(), Cache { size: 16, ratio: 0.9 });
+//! assert_eq!(
+//!     Cache::default_for_version(&Version::new(1, 5, 0)),
+//!     Cache { size: 8, ratio: 0.75 },
+//! );
+//! ```
+//! 
+//! ## `fancy_default::derive::defaults`
+//! 
+//! An attribute macro form of the derive macros above, using the same configuration syntax.
+//! Since it can rewrite the item it is attached to, it also:
+//! 
+//! - appends the default value of each field to its documentation,
+//!   like: ``Default: `8080` ``;
+//! - removes the `#[default]` helper attributes from the item,
+//!   unless a derive macro of this crate below `#[defaults]` reads them.
+//! 
+//! Fields and variants may be gated by `#[cfg]` like with the derive macros,
+//! except for the fields of tuple structs.
+//! 
+//! The traits to implement are listed as arguments,
+//! like: `#[defaults(Default, ConstDefault, DefaultSchema)]`.
+//! If no argument is given, only `Default` is implemented.
+//! 
+//! **Note**: Put `#[defaults]` above any `#[derive]` attribute of the item,
+//! so that other derive macros see the rewritten item.
+//! 
+//! **Basic Usage:**
+//! 
+//! ```rust
+//! #[fancy_default::defaults(Default, ConstDefault)]
+//! #[derive(Debug, PartialEq, Eq)]
+//! struct Config {
+//!     /// The port to listen on.
+//!     #[default = 8080]
+//!     port: u16,
+//! }
+//! 
+//! use fancy_default::ConstDefault;
+//! assert_eq!(Config::DEFAULT, Config { port: 8080 });
+//! assert_eq!(Config::default(), Config { port: 8080 });
+//! ```
+//! 
+//! ## `fancy_default::derive::structure`
+//! 
+//! Define structs with inline field default values, like: `port: u16 = 8080`,
+//! mirroring the unstable default field values syntax.
+//! The inline values are equivalent to `#[default(expr = <expr>)]`,
+//! and the item is then processed like [`defaults`](#fancy_defaultderivedefaults),
+//! with an optional `#[defaults(...)]` attribute listing the traits to implement.
+//! 
+//! **Note**: This is a function-like macro rather than an attribute macro,
+//! since the compiler rejects this field syntax on stable Rust
+//! before any attribute macro could rewrite it.
+//! 
+//! **Basic Usage:**
+//! 
+//! ```rust
+//! fancy_default::structure! {
+//!     #[defaults(Default)]
+//!     #[derive(Debug, PartialEq, Eq)]
+//!     pub struct Config {
+//!         pub port: u16 = 8080,
+//!         pub host: String = "localhost".into(),
+//!         pub verbose: bool,
+//!     }
+//! }
+//! 
+//! assert_eq!(
+//!     Config::default(),
+//!     Config {
+//!         port: 8080,
+//!         host: "localhost".to_owned(),
+//!         verbose: false,
+//!     }
+//! );
+//! ```
+//! 
 //! ## MSRV
 //! 
 //! The theoretical minimum rust version of this derived macro is 1.34,
 //! which allows passing `TokenStream` to `MetaList` from that version onwards.
+//! `AsyncDefault` and `DiffDefault` rely on `impl Trait` in trait return types,
+//! which is available since 1.75.
 //! 
 //! ## License
 //! 
@@ -147,10 +939,45 @@
 
 #![no_std]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+#[doc(hidden)]
+pub mod __private;
+/// Typestates used by the builders generated by `DefaultBuilder`.
+pub mod builder;
 /// Derive macros provided by the library.
 pub mod derive;
+/// Publishing the default values in JSON schemas generated by `schemars`.
+#[cfg(feature = "schemars")]
+pub mod schemars;
 /// `Default`-like traits implemented by the derive macros.
 pub mod traits;
+/// Versions selecting the defaults of `VersionedDefault`.
+pub mod version;
+/// Wrapper types customizing the default value of their inner value.
+pub mod wrapper;
 
-pub use derive::{ConstDefault, Default, VariantDefault};
-pub use traits::ConstDefault;
+pub use derive::{
+    defaults, structure, AsyncDefault, ConstDefault, DebugNonDefault, Default, DefaultBuilder,
+    DefaultPartial, DefaultSchema, DefaultWith, MergeDefaults, TryDefault, VariantDefault,
+    VersionedDefault,
+};
+#[cfg(feature = "alloc")]
+pub use derive::{ApplyOverrides, DiffDefault};
+#[cfg(feature = "serde")]
+pub use derive::{Deserialize, Serialize};
+#[cfg(feature = "schemars")]
+pub use self::schemars::JsonSchemaDefaults;
+#[cfg(feature = "schemars")]
+pub use derive::JsonSchemaDefaults;
+#[cfg(feature = "alloc")]
+pub use traits::{ApplyOverrides, DiffDefault};
+pub use traits::{
+    AsyncDefault, ConstDefault, DefaultPartial, DefaultProvider, DefaultSchema, DefaultWith,
+    MergeDefaults, Partial, TryDefault, VersionedDefault,
+};
+pub use version::Version;
+pub use wrapper::{Setting, WithDefault};
@@ -0,0 +1,56 @@
+use alloc::{string::ToString, vec::Vec};
+
+use ::schemars::Schema;
+use serde_json::Value;
+
+/// The serialized default values of a type, used to patch its JSON schema.
+pub trait JsonSchemaDefaults {
+    /// The serialized default values.
+    fn json_defaults() -> JsonDefault;
+}
+
+/// The serialized default values of a type.
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonDefault {
+    /// The default value of each field of a struct, by its serialized name.
+    ///
+    /// Fields that have no default value, or fail to serialize, are not included.
+    Fields(Vec<(&'static str, Value)>),
+    /// The default value of the type as a whole, such as the default variant of an enum.
+    Value(Value),
+    /// The default value failed to serialize.
+    Unavailable,
+}
+
+/// Set the `default` keyword of a schema from `T`'s default values.
+///
+/// For structs, each property with a default value gets its `default` keyword,
+/// and is removed from `required`, as the derived `Deserialize` fills it when missing.
+/// For other types, the `default` keyword is set on the schema itself.
+///
+/// Use it as the `transform` of `schemars`,
+/// like: `#[schemars(transform = fancy_default::schemars::add_defaults::<Self>)]`.
+pub fn add_defaults<T: JsonSchemaDefaults>(schema: &mut Schema) {
+    match T::json_defaults() {
+        JsonDefault::Fields(fields) => {
+            for (name, value) in fields {
+                if let Some(property) = schema
+                    .get_mut("properties")
+                    .and_then(Value::as_object_mut)
+                    .and_then(|properties| properties.get_mut(name))
+                    .and_then(Value::as_object_mut)
+                {
+                    property.insert("default".to_string(), value);
+                }
+
+                if let Some(required) = schema.get_mut("required").and_then(Value::as_array_mut) {
+                    required.retain(|field| field != name);
+                }
+            }
+        }
+        JsonDefault::Value(value) => {
+            schema.insert("default".to_string(), value);
+        }
+        JsonDefault::Unavailable => {}
+    }
+}
@@ -1,6 +1,266 @@
+use core::future::Future;
+
+use crate::version::Version;
+
+#[cfg(feature = "alloc")]
+use alloc::{
+    boxed::Box,
+    string::{String, ToString},
+    vec::Vec,
+};
+#[cfg(feature = "alloc")]
+use core::fmt::{self, Debug, Display};
+
 pub trait ConstDefault {
     const DEFAULT: Self;
 }
 
 /// Re-exporting `Default` to prevent naming pollution.
 pub use core::default::Default;
+
+/// A fallible version of [`Default`].
+pub trait TryDefault: Sized {
+    /// The error returned when a default value cannot be created.
+    type Error;
+
+    /// Try to create the default value.
+    fn try_default() -> Result<Self, Self::Error>;
+}
+
+/// A version of [`Default`] whose default value depends on a context, such as the configuration of the application.
+pub trait DefaultWith<Ctx: ?Sized> {
+    /// Create the default value from the context.
+    fn default_with(ctx: &Ctx) -> Self;
+}
+
+/// An asynchronous version of [`Default`], independent of any executor.
+pub trait AsyncDefault: Sized {
+    /// Create the default value asynchronously.
+    fn default_async() -> impl Future<Output = Self>;
+}
+
+/// A reusable strategy providing default values of `T`.
+///
+/// Used by `#[default(provider = <type>)]` and [`WithDefault`](crate::wrapper::WithDefault).
+pub trait DefaultProvider<T> {
+    /// Create the default value.
+    fn provide() -> T;
+}
+
+/// A type with a partial counterpart, whose fields are all optional.
+pub trait DefaultPartial: Sized {
+    /// The partial type, like `<Type>Partial` generated by `derive(DefaultPartial)`.
+    type Partial: Partial<Target = Self>;
+}
+
+/// A partial value, such as a single layer of a layered configuration.
+pub trait Partial: Default {
+    /// The complete type.
+    type Target;
+
+    /// Merge two layers, where the fields set in `other` take precedence.
+    fn merge(self, other: Self) -> Self;
+
+    /// Override the fields of `base` that are set in this layer.
+    fn apply(self, base: Self::Target) -> Self::Target;
+
+    /// Fill the fields that are not set with their default values.
+    fn resolve(self) -> Self::Target;
+}
+
+/// Layering values of the same type, where default values count as unset.
+pub trait MergeDefaults {
+    /// Copy each field of `overlay` into `self`, if it differs from its default value.
+    fn merge_non_default(&mut self, overlay: Self);
+
+    /// Copy each field of `overlay` into `self`, if it differs from the same field of `default`.
+    ///
+    /// This merges nested fields whose default values are overridden by the parent type.
+    fn merge_non_default_with(&mut self, overlay: Self, default: &Self);
+}
+
+/// The default value as of a version, keeping the older defaults of persisted values.
+pub trait VersionedDefault: Sized {
+    /// Create the default value as it was in `version`.
+    fn default_for_version(version: &Version) -> Self;
+}
+
+/// Comparing a value against its default value, field by field.
+#[cfg(feature = "alloc")]
+pub trait DiffDefault {
+    /// The fields whose values differ from their default values, in declaration order.
+    fn non_default_fields(&self) -> impl Iterator<Item = FieldDiff<'_>>;
+
+    /// The fields whose values differ from the same fields of `default`, in declaration order.
+    ///
+    /// This compares nested fields whose default values are overridden by the parent type.
+    fn fields_differing_from(&self, default: Self) -> impl Iterator<Item = FieldDiff<'_>>
+    where
+        Self: Sized;
+}
+
+/// A field whose value differs from its default value.
+#[cfg(feature = "alloc")]
+pub struct FieldDiff<'a> {
+    /// The path of the field, like `server.port`.
+    pub path: String,
+    /// The current value of the field.
+    pub value: &'a dyn Debug,
+    /// The default value of the field.
+    pub default: Box<dyn Debug + 'a>,
+}
+
+#[cfg(feature = "alloc")]
+impl Debug for FieldDiff<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FieldDiff")
+            .field("path", &self.path)
+            .field("value", &self.value)
+            .field("default", &self.default)
+            .finish()
+    }
+}
+
+/// Overriding fields by dotted paths, like `server.tls.port`, with values parsed by `FromStr`.
+#[cfg(feature = "alloc")]
+pub trait ApplyOverrides {
+    /// Parse `value` into the field at `key`.
+    fn apply(&mut self, key: &str, value: &str) -> Result<(), OverrideError>;
+
+    /// Apply the environment variables starting with `prefix`,
+    /// where `__` separates the path segments, like `APP_SERVER__PORT` for `server.port`
+    /// with the prefix `APP_`.
+    ///
+    /// The variables are given by the caller, like `std::env::vars()`.
+    fn apply_env<K, V>(
+        &mut self,
+        prefix: &str,
+        vars: impl IntoIterator<Item = (K, V)>,
+    ) -> Result<(), OverrideError>
+    where
+        Self: Sized,
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        for (name, value) in vars {
+            if let Some(path) = name.as_ref().strip_prefix(prefix) {
+                let key = path
+                    .split("__")
+                    .map(|segment| segment.to_ascii_lowercase())
+                    .collect::<Vec<_>>()
+                    .join(".");
+                self.apply(&key, value.as_ref())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Apply the `-o key=value` pairs of command line arguments, ignoring other arguments.
+    ///
+    /// The arguments are given by the caller, like `std::env::args().skip(1)`.
+    fn apply_args<A>(&mut self, args: impl IntoIterator<Item = A>) -> Result<(), OverrideError>
+    where
+        Self: Sized,
+        A: AsRef<str>,
+    {
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            if arg.as_ref() != "-o" {
+                continue;
+            }
+            let pair = args
+                .next()
+                .ok_or_else(|| OverrideError::MalformedArgument("-o".to_string()))?;
+            let (key, value) = pair
+                .as_ref()
+                .split_once('=')
+                .ok_or_else(|| OverrideError::MalformedArgument(pair.as_ref().to_string()))?;
+            self.apply(key, value)?;
+        }
+        Ok(())
+    }
+}
+
+/// The error returned by [`ApplyOverrides`].
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum OverrideError {
+    /// No field is found at the path.
+    UnknownKey(String),
+    /// The value cannot be parsed into the field.
+    InvalidValue {
+        /// The path of the field.
+        key: String,
+        /// The error message of `FromStr`.
+        message: String,
+    },
+    /// A command line argument is not a `-o key=value` pair.
+    MalformedArgument(String),
+}
+
+#[cfg(feature = "alloc")]
+impl OverrideError {
+    /// Prefix the path of the error with the name of the parent field.
+    pub fn nested(self, parent: &str) -> Self {
+        match self {
+            OverrideError::UnknownKey(key) => {
+                OverrideError::UnknownKey(alloc::format!("{}.{}", parent, key))
+            }
+            OverrideError::InvalidValue { key, message } => OverrideError::InvalidValue {
+                key: alloc::format!("{}.{}", parent, key),
+                message,
+            },
+            error => error,
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Display for OverrideError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OverrideError::UnknownKey(key) => write!(f, "unknown key `{}`", key),
+            OverrideError::InvalidValue { key, message } => {
+                write!(f, "invalid value for `{}`: {}", key, message)
+            }
+            OverrideError::MalformedArgument(arg) => {
+                write!(f, "expected `-o key=value`, found `{}`", arg)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for OverrideError {}
+
+/// Runtime metadata about the default value of a type.
+pub trait DefaultSchema {
+    /// The default value configuration of each field, in declaration order.
+    ///
+    /// For enums, these are the fields of the default variant.
+    fn default_schema() -> &'static [FieldDefault];
+
+    /// The name of the default variant, if the type is an enum.
+    fn default_variant() -> Option<&'static str> {
+        None
+    }
+}
+
+/// The default value configuration of a single field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct FieldDefault {
+    /// The name of the field, or its index for tuple fields.
+    pub name: &'static str,
+    /// The type of the field, as written in the source.
+    pub ty: &'static str,
+    /// The default value expression, as written in the source.
+    ///
+    /// This is empty for `required` fields.
+    pub expr: &'static str,
+    /// Whether the field is `required`, and thus has no default value.
+    pub required: bool,
+    /// The doc comments of the field, one line each.
+    pub doc: &'static str,
+}
@@ -0,0 +1,69 @@
+use core::fmt;
+use core::str::FromStr;
+
+/// A `major.minor.patch` version, ordered numerically.
+///
+/// Parsed from `major`, `major.minor` or `major.minor.patch`,
+/// where the missing components are `0`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Version {
+    /// The major version.
+    pub major: u64,
+    /// The minor version.
+    pub minor: u64,
+    /// The patch version.
+    pub patch: u64,
+}
+
+impl Version {
+    /// Create a version from its components.
+    pub const fn new(major: u64, minor: u64, patch: u64) -> Self {
+        Version {
+            major,
+            minor,
+            patch,
+        }
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// The error of parsing a [`Version`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseVersionError;
+
+impl fmt::Display for ParseVersionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("expected a version like `major[.minor[.patch]]`")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseVersionError {}
+
+impl FromStr for Version {
+    type Err = ParseVersionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut components = [0; 3];
+        let mut parts = s.split('.');
+        for component in components.iter_mut() {
+            match parts.next() {
+                Some(part) if !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit()) => {
+                    *component = part.parse().map_err(|_| ParseVersionError)?;
+                }
+                Some(_) => return Err(ParseVersionError),
+                None => break,
+            }
+        }
+        if parts.next().is_some() {
+            return Err(ParseVersionError);
+        }
+        let [major, minor, patch] = components;
+        Ok(Version::new(major, minor, patch))
+    }
+}
@@ -0,0 +1,216 @@
+use core::cmp::Ordering;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::marker::PhantomData;
+use core::ops::{Deref, DerefMut};
+use core::str::FromStr;
+
+use crate::traits::{Default, DefaultProvider};
+
+/// A transparent wrapper of `T`, whose `Default` impl is given by the provider `P`.
+///
+/// All other traits are implemented as if by `T` itself, regardless of `P`.
+#[repr(transparent)]
+pub struct WithDefault<T, P> {
+    value: T,
+    provider: PhantomData<fn() -> P>,
+}
+
+impl<T, P> WithDefault<T, P> {
+    /// Wrap a value.
+    pub const fn new(value: T) -> Self {
+        WithDefault {
+            value,
+            provider: PhantomData,
+        }
+    }
+
+    /// Unwrap the value.
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T, P: DefaultProvider<T>> Default for WithDefault<T, P> {
+    fn default() -> Self {
+        WithDefault::new(P::provide())
+    }
+}
+
+impl<T, P> From<T> for WithDefault<T, P> {
+    fn from(value: T) -> Self {
+        WithDefault::new(value)
+    }
+}
+
+impl<T, P> Deref for WithDefault<T, P> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T, P> DerefMut for WithDefault<T, P> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+impl<T, P> AsRef<T> for WithDefault<T, P> {
+    fn as_ref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T, P> AsMut<T> for WithDefault<T, P> {
+    fn as_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+impl<T: fmt::Debug, P> fmt::Debug for WithDefault<T, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.value.fmt(f)
+    }
+}
+
+impl<T: fmt::Display, P> fmt::Display for WithDefault<T, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.value.fmt(f)
+    }
+}
+
+impl<T: Clone, P> Clone for WithDefault<T, P> {
+    fn clone(&self) -> Self {
+        WithDefault::new(self.value.clone())
+    }
+}
+
+impl<T: Copy, P> Copy for WithDefault<T, P> {}
+
+impl<T: PartialEq, P> PartialEq for WithDefault<T, P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<T: Eq, P> Eq for WithDefault<T, P> {}
+
+impl<T: PartialOrd, P> PartialOrd for WithDefault<T, P> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.value.partial_cmp(&other.value)
+    }
+}
+
+impl<T: Ord, P> Ord for WithDefault<T, P> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.value.cmp(&other.value)
+    }
+}
+
+impl<T: Hash, P> Hash for WithDefault<T, P> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.value.hash(state)
+    }
+}
+
+/// A value remembering whether it was explicitly set or came from the default.
+///
+/// The derive macros initialize `Setting` fields with [`Setting::Default`],
+/// while `From`, `FromStr` and deserializing give [`Setting::Explicit`].
+///
+/// Values of different origins are not equal to each other,
+/// so `Setting` is not ordered, as ordering by the value alone would disagree with `Eq`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Setting<T> {
+    /// The default value.
+    Default(T),
+    /// A value set explicitly.
+    Explicit(T),
+}
+
+impl<T> Setting<T> {
+    /// Whether the value was explicitly set.
+    pub const fn is_explicit(&self) -> bool {
+        matches!(self, Setting::Explicit(_))
+    }
+
+    /// Whether the value is the default.
+    pub const fn is_default(&self) -> bool {
+        matches!(self, Setting::Default(_))
+    }
+
+    /// Get the value.
+    pub const fn get(&self) -> &T {
+        match self {
+            Setting::Default(value) | Setting::Explicit(value) => value,
+        }
+    }
+
+    /// Explicitly set the value.
+    pub fn set(&mut self, value: T) {
+        *self = Setting::Explicit(value);
+    }
+
+    /// Unwrap the value.
+    pub fn into_inner(self) -> T {
+        match self {
+            Setting::Default(value) | Setting::Explicit(value) => value,
+        }
+    }
+}
+
+impl<T: Default> Default for Setting<T> {
+    fn default() -> Self {
+        Setting::Default(T::default())
+    }
+}
+
+impl<T> From<T> for Setting<T> {
+    fn from(value: T) -> Self {
+        Setting::Explicit(value)
+    }
+}
+
+impl<T> Deref for Setting<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.get()
+    }
+}
+
+impl<T> AsRef<T> for Setting<T> {
+    fn as_ref(&self) -> &T {
+        self.get()
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Setting<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.get().fmt(f)
+    }
+}
+
+impl<T: FromStr> FromStr for Setting<T> {
+    type Err = T::Err;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().map(Setting::Explicit)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize> serde::Serialize for Setting<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.get().serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: serde::Deserialize<'de>> serde::Deserialize<'de> for Setting<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        T::deserialize(deserializer).map(Setting::Explicit)
+    }
+}
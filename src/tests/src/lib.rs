@@ -182,3 +182,2099 @@ fn test_derive_variant() {
         }
     );
 }
+
+#[test]
+fn test_derive_struct_nested() {
+    use fancy_default::derive::Default;
+
+    #[derive(Debug, Default, PartialEq)]
+    struct Backoff {
+        #[default = 1.5]
+        factor: f64,
+        #[default = 100]
+        initial_ms: u64,
+    }
+
+    #[derive(Debug, Default, PartialEq)]
+    struct RetryPolicy {
+        #[default = 3]
+        max_retries: u32,
+        backoff: Backoff,
+    }
+
+    #[derive(Debug, Default, PartialEq)]
+    struct Client {
+        #[default(nested(max_retries = 5, backoff.factor = 2.0))]
+        retry: RetryPolicy,
+        #[default(expr = RetryPolicy { max_retries: 0, ..Default::default() }, nested(backoff.initial_ms = 10))]
+        fallback: RetryPolicy,
+    }
+
+    assert_eq!(
+        Client {
+            retry: RetryPolicy {
+                max_retries: 5,
+                backoff: Backoff {
+                    factor: 2.0,
+                    initial_ms: 100,
+                },
+            },
+            fallback: RetryPolicy {
+                max_retries: 0,
+                backoff: Backoff {
+                    factor: 1.5,
+                    initial_ms: 10,
+                },
+            },
+        },
+        Default::default(),
+    );
+}
+
+#[test]
+fn test_derive_struct_nested_const() {
+    use fancy_default::ConstDefault;
+
+    #[derive(Debug, ConstDefault, PartialEq, Eq)]
+    struct Inner(#[default = 1] u8, #[default = 2] u8);
+
+    #[derive(Debug, ConstDefault, PartialEq, Eq)]
+    struct Outer {
+        #[default(expr = Inner::DEFAULT, nested(1 = 20))]
+        inner: Inner,
+    }
+
+    assert_eq!(Outer { inner: Inner(1, 20) }, Outer::DEFAULT);
+}
+
+#[test]
+fn test_derive_enum_nested() {
+    use fancy_default::derive::Default;
+
+    #[derive(Debug, Default, PartialEq, Eq)]
+    struct Inner {
+        #[default = 1]
+        id: usize,
+        #[default = 2]
+        count: usize,
+    }
+
+    #[derive(Debug, Default, PartialEq, Eq)]
+    enum Enum {
+        #[default]
+        Tuple(#[default(nested(count = 10))] Inner),
+    }
+
+    assert_eq!(Enum::Tuple(Inner { id: 1, count: 10 }), Default::default());
+}
+
+#[test]
+fn test_derive_enum_container_variant() {
+    use fancy_default::derive::Default;
+
+    #[derive(Debug, Default, PartialEq, Eq)]
+    #[default(variant = Tuple)]
+    enum Enum {
+        Plain,
+        Tuple(#[default = 10] usize),
+    }
+
+    assert_eq!(Enum::Tuple(10), Default::default());
+}
+
+#[test]
+fn test_derive_enum_container_variant_cfg_attr() {
+    use fancy_default::derive::Default;
+
+    #[derive(Debug, Default, PartialEq, Eq)]
+    #[cfg_attr(any(), default(variant = Plain))]
+    #[cfg_attr(all(), default(variant = Tuple))]
+    enum Enum {
+        Plain,
+        Tuple(#[default = 10] usize),
+    }
+
+    assert_eq!(Enum::Tuple(10), Default::default());
+}
+
+#[test]
+fn test_derive_enum_container_expr() {
+    use fancy_default::ConstDefault;
+
+    #[derive(Debug, ConstDefault, PartialEq, Eq)]
+    #[default(variant = Tuple, expr = Enum::Tuple(3))]
+    enum Enum {
+        Plain,
+        Tuple(#[default = 10] usize),
+    }
+
+    assert_eq!(Enum::Tuple(3), Enum::DEFAULT);
+}
+
+#[test]
+fn test_derive_enum_container_expr_const() {
+    use fancy_default::{ConstDefault, DefaultSchema};
+
+    fn selected(verbose: bool) -> Selected {
+        if verbose {
+            Selected::Debug(Level::Debug(2))
+        } else {
+            Selected::Debug(Level::Info)
+        }
+    }
+
+    // constants and helper calls are not checked against the selected variant
+    #[derive(Debug, ConstDefault, PartialEq, Eq)]
+    #[default(variant = Debug, expr = Level::VERBOSE)]
+    enum Level {
+        Info,
+        Debug(usize),
+    }
+
+    impl Level {
+        const VERBOSE: Level = Level::Debug(3);
+    }
+
+    #[derive(Debug, fancy_default::Default, DefaultSchema, PartialEq, Eq)]
+    #[default(variant = Debug, expr = selected(true))]
+    enum Selected {
+        Info,
+        Debug(Level),
+    }
+
+    assert_eq!(Level::Debug(3), Level::DEFAULT);
+    assert_eq!(Selected::Debug(Level::Debug(2)), Selected::default());
+    assert_eq!(Selected::default_variant(), Some("Debug"));
+}
+
+/// The schema entries as `(name, ty, expr, required, doc)`.
+fn schema_entries(
+    schema: &[fancy_default::traits::FieldDefault],
+) -> Vec<(&str, &str, &str, bool, &str)> {
+    schema
+        .iter()
+        .map(|field| (field.name, field.ty, field.expr, field.required, field.doc))
+        .collect()
+}
+
+#[test]
+fn test_derive_schema_struct() {
+    use fancy_default::DefaultSchema;
+
+    #[derive(DefaultSchema)]
+    struct Struct {
+        /// The name.
+        ///
+        /// Some more lines.
+        #[default(expr = "123".to_owned())]
+        name: String,
+        #[default(nested(count = 1))]
+        inner: Inner,
+        id: usize,
+    }
+
+    #[derive(DefaultSchema)]
+    struct Inner {
+        count: usize,
+    }
+
+    assert_eq!(
+        schema_entries(Struct::default_schema()),
+        [
+            (
+                "name",
+                "String",
+                "\"123\".to_owned()",
+                false,
+                "The name.\n\nSome more lines.",
+            ),
+            ("inner", "Inner", "Default::default(), nested(count = 1)", false, ""),
+            ("id", "usize", "Default::default()", false, ""),
+        ]
+    );
+    assert_eq!(Struct::default_variant(), None);
+}
+
+#[test]
+fn test_derive_schema_enum() {
+    use fancy_default::DefaultSchema;
+
+    #[derive(DefaultSchema)]
+    enum Enum {
+        Plain,
+        #[default]
+        Tuple(#[default = 10] usize),
+    }
+
+    assert_eq!(
+        schema_entries(Enum::default_schema()),
+        [("0", "usize", "10", false, "")]
+    );
+    assert_eq!(Enum::default_variant(), Some("Tuple"));
+}
+
+#[test]
+fn test_attribute_defaults_struct() {
+    #[fancy_default::defaults]
+    #[derive(Debug, PartialEq, Eq)]
+    struct Struct {
+        /// The name.
+        #[default(expr = "123".to_owned())]
+        name: String,
+        #[default = 10]
+        id: usize,
+    }
+
+    assert_eq!(
+        Struct {
+            name: "123".to_owned(),
+            id: 10
+        },
+        Default::default(),
+    );
+}
+
+#[test]
+fn test_attribute_defaults_enum() {
+    use fancy_default::{ConstDefault, DefaultSchema};
+
+    #[fancy_default::defaults(Default, ConstDefault, DefaultSchema)]
+    #[derive(Debug, PartialEq, Eq)]
+    enum Enum {
+        Plain,
+        #[default]
+        Tuple(#[default = 10] usize),
+    }
+
+    assert_eq!(Enum::Tuple(10), Default::default());
+    assert_eq!(Enum::Tuple(10), Enum::DEFAULT);
+    assert_eq!(Enum::default_variant(), Some("Tuple"));
+}
+
+#[test]
+fn test_attribute_defaults_docs() {
+    use fancy_default::DefaultSchema;
+
+    // the derive below runs on the item rewritten by `#[defaults]`, so it sees the injected docs
+    #[fancy_default::defaults(Default)]
+    #[derive(fancy_default::derive::DefaultSchema)]
+    struct Client {
+        /// The port to connect to.
+        #[default = 8080]
+        port: u16,
+        #[default(required)]
+        endpoint: String,
+    }
+
+    let schema = Client::default_schema();
+    assert_eq!(schema[0].doc, "The port to connect to.\n\nDefault: `8080`");
+    assert_eq!(schema[1].doc, "Required.");
+}
+
+#[test]
+fn test_attribute_defaults_strips_helpers() {
+    use fancy_default::DefaultSchema;
+
+    // `Debug` does not register the `default` helper, so a leftover one would not compile
+    #[fancy_default::defaults(Default)]
+    #[derive(Debug, PartialEq, Eq)]
+    enum Mode {
+        #[default]
+        Fast,
+        Slow,
+    }
+
+    // the helpers are kept for the derive below `#[defaults]`
+    #[fancy_default::defaults(Default)]
+    #[derive(fancy_default::derive::DefaultSchema)]
+    struct Client {
+        #[default = 8080]
+        port: u16,
+    }
+
+    assert_eq!(Mode::Fast, Mode::default());
+    assert_eq!(Client::default().port, 8080);
+    assert_eq!(Client::default_schema()[0].expr, "8080");
+}
+
+#[test]
+fn test_structure_inline_defaults() {
+    use fancy_default::ConstDefault;
+
+    fancy_default::structure! {
+        #[derive(Debug, PartialEq, Eq)]
+        struct Config {
+            port: u16 = 8080,
+            host: String = "localhost".into(),
+            #[default = 10]
+            retries: usize,
+            verbose: bool,
+        }
+
+        #[defaults(Default, ConstDefault)]
+        #[derive(Debug, PartialEq, Eq)]
+        struct Limits<'a>
+        where
+            'a: 'static,
+        {
+            name: &'a str = "limits",
+            max: u32 = 64,
+        }
+    }
+
+    assert_eq!(
+        Config {
+            port: 8080,
+            host: "localhost".to_owned(),
+            retries: 10,
+            verbose: false,
+        },
+        Default::default(),
+    );
+    assert_eq!(
+        Limits {
+            name: "limits",
+            max: 64
+        },
+        Limits::DEFAULT,
+    );
+}
+
+#[test]
+fn test_derive_struct_make() {
+    use fancy_default::derive::Default;
+
+    // not a `const fn`, so `CONFIG` only compiles if this is not evaluated
+    fn not_evaluated() -> &'static str {
+        panic!("should not be evaluated")
+    }
+
+    #[derive(Debug, Default, PartialEq, Eq)]
+    #[default(make = config)]
+    struct Config {
+        #[default = 8080]
+        port: u16,
+        #[default = "localhost"]
+        host: &'static str,
+        #[default(expr = not_evaluated())]
+        name: &'static str,
+    }
+
+    const CONFIG: Config = config! { name: "const" };
+
+    assert_eq!(
+        Config {
+            port: 8080,
+            host: "localhost",
+            name: "const",
+        },
+        CONFIG,
+    );
+    assert_eq!(
+        Config {
+            port: 1,
+            host: "localhost",
+            name: "runtime",
+        },
+        config! { port: 1, name: "runtime", },
+    );
+}
+
+#[cfg(test)]
+mod limits {
+    use fancy_default::ConstDefault;
+
+    #[derive(Debug, ConstDefault, PartialEq, Eq)]
+    #[default(make = limits)]
+    pub struct Limits {
+        #[default = 64]
+        pub max: u32,
+        #[default(required)]
+        pub name: &'static str,
+    }
+
+    #[derive(Debug, fancy_default::Default, ConstDefault, PartialEq, Eq)]
+    #[default(const, make = range)]
+    pub struct Range {
+        #[default = 1]
+        pub min: u32,
+        #[default = 10]
+        pub max: u32,
+    }
+}
+
+#[test]
+fn test_derive_struct_make_const() {
+    // invoked from another module, where the structs are imported along with the macros
+    use crate::limits::{limits, range, Limits, Range};
+
+    const LIMITS: Limits = limits! { name: "default" };
+    const RANGE: Range = range! { max: 5 };
+
+    assert_eq!(
+        Limits {
+            max: 64,
+            name: "default",
+        },
+        LIMITS,
+    );
+    assert_eq!(Range { min: 1, max: 5 }, RANGE);
+}
+
+#[test]
+fn test_derive_struct_make_generic() {
+    use fancy_default::derive::Default;
+
+    #[derive(Debug, Default, PartialEq, Eq)]
+    #[default(make = holder)]
+    struct Holder<'a, T> {
+        value: Option<T>,
+        #[default = "none"]
+        label: &'a str,
+        #[default = 1]
+        count: u8,
+    }
+
+    let holder: Holder<u8> = holder! { count: 2 };
+    assert_eq!(
+        Holder {
+            value: None,
+            label: "none",
+            count: 2,
+        },
+        holder,
+    );
+    assert_eq!(Some(3), holder! { value: Some(3u16) }.value);
+}
+
+#[test]
+fn test_derive_struct_required() {
+    use fancy_default::derive::Default;
+
+    #[derive(Debug, Default, PartialEq, Eq)]
+    #[default(make = client)]
+    struct Client {
+        #[default = 3]
+        retries: usize,
+        #[default(required)]
+        endpoint: String,
+        #[default(required)]
+        token: u64,
+    }
+
+    #[derive(Debug, Default, PartialEq, Eq)]
+    struct Tuple(#[default(required)] u8, #[default = 2] u8);
+
+    assert_eq!(
+        Client {
+            retries: 3,
+            endpoint: "localhost".to_owned(),
+            token: 42,
+        },
+        Client::new("localhost".to_owned(), 42),
+    );
+    assert_eq!(
+        Client {
+            retries: 3,
+            endpoint: "localhost".to_owned(),
+            token: 42,
+        },
+        client! { token: 42, endpoint: "localhost".to_owned() },
+    );
+    assert_eq!(Tuple(1, 2), Tuple::new(1));
+}
+
+#[test]
+fn test_derive_struct_required_const() {
+    use fancy_default::ConstDefault;
+
+    #[derive(Debug, ConstDefault, PartialEq, Eq)]
+    struct Client<'a> {
+        #[default(required)]
+        endpoint: &'a str,
+        #[default = 3]
+        retries: usize,
+    }
+
+    const CLIENT: Client<'static> = Client::new("localhost");
+
+    assert_eq!(
+        Client {
+            endpoint: "localhost",
+            retries: 3,
+        },
+        CLIENT,
+    );
+}
+
+#[test]
+fn test_derive_struct_required_both() {
+    use fancy_default::{ConstDefault, Default};
+
+    #[derive(Debug, Default, ConstDefault, PartialEq, Eq)]
+    #[default(const)]
+    struct Client<'a> {
+        #[default(required)]
+        endpoint: &'a str,
+        #[default = 3]
+        retries: usize,
+    }
+
+    #[fancy_default::defaults(Default, ConstDefault)]
+    #[derive(Debug, PartialEq, Eq)]
+    struct Server {
+        #[default(required)]
+        port: u16,
+        #[default = 4]
+        workers: usize,
+    }
+
+    const CLIENT: Client<'static> = Client::new("localhost");
+    const SERVER: Server = Server::new(8080);
+
+    assert_eq!(
+        Client {
+            endpoint: "localhost",
+            retries: 3,
+        },
+        CLIENT,
+    );
+    assert_eq!(
+        Server {
+            port: 8080,
+            workers: 4,
+        },
+        SERVER,
+    );
+}
+
+#[test]
+fn test_derive_struct_const_default_only() {
+    use fancy_default::Default;
+
+    // the items shared with `ConstDefault` are still generated without it
+    #[derive(Debug, Default, PartialEq, Eq)]
+    #[default(const, make = worker, with)]
+    struct Worker {
+        #[default(required)]
+        id: u32,
+        #[default = 2]
+        threads: usize,
+    }
+
+    const WORKER: Worker = Worker::new(1);
+
+    assert_eq!(Worker { id: 1, threads: 2 }, WORKER);
+    assert_eq!(Worker { id: 1, threads: 8 }, WORKER.with_threads(8usize));
+    assert_eq!(Worker { id: 3, threads: 2 }, worker! { id: 3 });
+}
+
+#[test]
+fn test_derive_builder_struct() {
+    use fancy_default::DefaultBuilder;
+
+    #[derive(Debug, DefaultBuilder, PartialEq, Eq)]
+    struct Client<'a, T: Clone> {
+        #[default(required)]
+        endpoint: &'a str,
+        #[default = 3]
+        retries: usize,
+        #[default(required)]
+        payload: T,
+        tag: Option<T>,
+    }
+
+    let client = Client::builder()
+        .payload(vec![1u8])
+        .retries(5)
+        .endpoint("localhost")
+        .build();
+
+    assert_eq!(
+        Client {
+            endpoint: "localhost",
+            retries: 5,
+            payload: vec![1],
+            tag: None,
+        },
+        client,
+    );
+}
+
+#[test]
+fn test_derive_builder_tuple_struct() {
+    use fancy_default::DefaultBuilder;
+
+    #[derive(Debug, DefaultBuilder, PartialEq, Eq)]
+    struct Struct(#[default(expr = "123".to_owned())] String, #[default = 10] usize);
+
+    #[derive(Debug, DefaultBuilder, PartialEq, Eq)]
+    struct Unit;
+
+    assert_eq!(Struct("123".to_owned(), 1), Struct::builder().field_1(1).build());
+    assert_eq!(Unit, Unit::builder().build());
+}
+
+#[test]
+fn test_derive_builder_raw_identifier() {
+    use fancy_default::DefaultBuilder;
+
+    #[derive(Debug, DefaultBuilder, PartialEq, Eq)]
+    struct Token {
+        #[default(required)]
+        r#type: u8,
+        #[default = 1]
+        r#loop: u8,
+    }
+
+    assert_eq!(
+        Token { r#type: 2, r#loop: 1 },
+        Token::builder().r#type(2).build(),
+    );
+}
+
+#[test]
+fn test_derive_builder_enum() {
+    use fancy_default::DefaultBuilder;
+
+    #[derive(Debug, DefaultBuilder, PartialEq, Eq)]
+    enum Enum {
+        Plain,
+        Struct {
+            #[default(expr = "123".to_owned())]
+            name: String,
+            #[default(required)]
+            id: usize,
+        },
+        Tuple(#[default = 10] usize),
+    }
+
+    assert_eq!(Enum::Plain, Enum::plain_builder().build());
+    assert_eq!(
+        Enum::Struct {
+            name: "123".to_owned(),
+            id: 1
+        },
+        Enum::struct_builder().id(1).build()
+    );
+    assert_eq!(Enum::Tuple(10), Enum::tuple_builder().build());
+}
+
+#[test]
+fn test_derive_struct_with_setters() {
+    use fancy_default::derive::Default;
+
+    #[derive(Debug, Default, PartialEq, Eq)]
+    #[default(with)]
+    struct Config {
+        #[default = 8080]
+        port: u16,
+        #[default(expr = "localhost".to_owned())]
+        host: String,
+    }
+
+    #[derive(Debug, Default, PartialEq, Eq)]
+    #[default(with(vis = "pub(crate)", into = false))]
+    struct Limits {
+        #[default = 64]
+        max: u32,
+    }
+
+    assert_eq!(
+        Config {
+            port: 9000,
+            host: "example.com".to_owned(),
+        },
+        Config::default().with_port(9000u16).with_host("example.com"),
+    );
+    assert_eq!(Limits { max: 1 }, Limits::default().with_max(1));
+}
+
+#[test]
+fn test_derive_struct_with_setters_const() {
+    use fancy_default::ConstDefault;
+
+    #[derive(Debug, ConstDefault, PartialEq, Eq)]
+    #[default(with(const))]
+    struct Config<'a> {
+        #[default = 8080]
+        port: u16,
+        #[default = "localhost"]
+        host: &'a str,
+    }
+
+    const CONFIG: Config<'static> = Config::DEFAULT.with_port(9000);
+
+    assert_eq!(
+        Config {
+            port: 9000,
+            host: "localhost",
+        },
+        CONFIG,
+    );
+}
+
+#[test]
+fn test_derive_struct_with_setters_either_derive() {
+    use fancy_default::{ConstDefault, Default};
+
+    #[derive(Debug, Default, PartialEq, Eq)]
+    #[default(with(const))]
+    struct Runtime {
+        #[default = 8080]
+        port: u16,
+    }
+
+    #[derive(Debug, ConstDefault, PartialEq, Eq)]
+    #[default(with)]
+    struct Constant {
+        #[default = 8080]
+        port: u16,
+    }
+
+    #[derive(Debug, Default, ConstDefault, PartialEq, Eq)]
+    #[default(const, with(const))]
+    struct Both {
+        #[default = 8080]
+        port: u16,
+    }
+
+    const RUNTIME: Runtime = Runtime { port: 1 }.with_port(9000);
+    const BOTH: Both = Both::DEFAULT.with_port(9000);
+
+    assert_eq!(Runtime { port: 9000 }, RUNTIME);
+    assert_eq!(Constant { port: 9000 }, Constant::DEFAULT.with_port(9000u16));
+    assert_eq!(Both { port: 9000 }, BOTH);
+    assert_eq!(Both { port: 1 }, Both::default().with_port(1));
+}
+
+#[test]
+fn test_derive_try_default_struct() {
+    use fancy_default::TryDefault;
+    use std::net::{AddrParseError, SocketAddr};
+    use std::num::ParseIntError;
+
+    #[derive(Debug, TryDefault, PartialEq, Eq)]
+    struct Config {
+        #[default(try = "127.0.0.1:80".parse(), error = AddrParseError)]
+        addr: SocketAddr,
+        #[default(try = "8080".parse())]
+        port: u16,
+        #[default = 3]
+        retries: usize,
+    }
+
+    #[derive(Debug, TryDefault, PartialEq, Eq)]
+    struct Broken(
+        #[default(try = "x".parse())] u8,
+        #[default(try = "1".parse())] u8,
+        #[default(try = "y".parse::<u8>())] u8,
+    );
+
+    assert_eq!(
+        Ok(Config {
+            addr: "127.0.0.1:80".parse().unwrap(),
+            port: 8080,
+            retries: 3,
+        }),
+        Config::try_default().map_err(|_| ()),
+    );
+
+    let error: BrokenDefaultError = Broken::try_default().unwrap_err();
+    assert!(error.field_0.is_some());
+    assert!(error.field_1.is_none());
+    assert!(error.field_2.is_some());
+    assert_eq!(
+        error.to_string(),
+        "failed to create the default value of `Broken`: \
+         `field_0`: invalid digit found in string, \
+         `field_2`: invalid digit found in string",
+    );
+
+    let _: Option<ParseIntError> = error.field_0;
+}
+
+#[test]
+fn test_derive_try_default_generic() {
+    use fancy_default::TryDefault;
+    use std::error::Error;
+    use std::fmt::Debug;
+    use std::str::FromStr;
+
+    #[derive(Debug, TryDefault, PartialEq, Eq)]
+    struct Limit<T, U>
+    where
+        T: FromStr,
+        U: Default,
+    {
+        #[default(try = "10".parse())]
+        max: T,
+        #[default(try = "x".parse())]
+        port: u16,
+        extra: U,
+    }
+
+    fn assert_error<E: Error>(_: &E) {}
+
+    let error: LimitDefaultError<u8, ()> = Limit::<u8, ()>::try_default().unwrap_err();
+    assert!(error.max.is_none());
+    assert!(error.port.is_some());
+    assert_error(&error);
+    assert_eq!(
+        error.to_string(),
+        "failed to create the default value of `Limit`: \
+         `port`: invalid digit found in string",
+    );
+
+    let error = Limit::<bool, ()>::try_default().unwrap_err();
+    assert!(error.max.is_some());
+    let _: &dyn Debug = &error;
+}
+
+#[test]
+fn test_derive_try_default_infallible() {
+    use fancy_default::TryDefault;
+
+    #[derive(Debug, TryDefault, PartialEq, Eq)]
+    enum Enum {
+        Plain,
+        #[default]
+        Tuple(#[default = 10] usize),
+    }
+
+    let value: Result<Enum, core::convert::Infallible> = Enum::try_default();
+    assert_eq!(Ok(Enum::Tuple(10)), value);
+}
+
+#[test]
+fn test_derive_default_with_struct() {
+    use fancy_default::DefaultWith;
+    use std::path::PathBuf;
+
+    struct AppCtx {
+        data_dir: PathBuf,
+        workers: usize,
+    }
+
+    #[derive(Debug, DefaultWith, PartialEq, Eq)]
+    struct Cache {
+        #[default(ctx = |c: &AppCtx| c.data_dir.join("cache"))]
+        dir: PathBuf,
+        #[default = 64]
+        capacity: usize,
+    }
+
+    #[derive(Debug, DefaultWith, PartialEq, Eq)]
+    #[default(ctx = AppCtx)]
+    struct Config {
+        cache: Cache,
+        #[default(ctx = |c| c.workers * 2)]
+        threads: usize,
+        name: String,
+    }
+
+    let ctx = AppCtx {
+        data_dir: PathBuf::from("/var/app"),
+        workers: 4,
+    };
+    assert_eq!(
+        Config {
+            cache: Cache {
+                dir: PathBuf::from("/var/app/cache"),
+                capacity: 64,
+            },
+            threads: 8,
+            name: String::new(),
+        },
+        Config::default_with(&ctx),
+    );
+}
+
+#[test]
+fn test_derive_default_with_enum() {
+    use fancy_default::DefaultWith;
+
+    #[derive(Debug, DefaultWith, PartialEq, Eq)]
+    #[default(ctx = str)]
+    enum Greeting {
+        Silent,
+        #[default]
+        Hello(#[default(ctx = |name| format!("Hello, {}!", name))] String, u8),
+    }
+
+    assert_eq!(
+        Greeting::Hello("Hello, world!".to_owned(), 0),
+        Greeting::default_with("world"),
+    );
+}
+
+/// A minimal executor, polling the future until it completes.
+fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    use std::task::{Context, Poll, Waker};
+
+    let mut future = std::pin::pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
+
+#[test]
+fn test_derive_async_default_struct() {
+    use fancy_default::AsyncDefault;
+    use std::cell::RefCell;
+    use std::future::poll_fn;
+    use std::task::Poll;
+
+    thread_local! {
+        static LOG: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+    }
+
+    async fn open(name: &str) -> String {
+        LOG.with(|log| log.borrow_mut().push(format!("start {}", name)));
+        let mut yielded = false;
+        poll_fn(|_| {
+            if yielded {
+                Poll::Ready(())
+            } else {
+                yielded = true;
+                Poll::Pending
+            }
+        })
+        .await;
+        LOG.with(|log| log.borrow_mut().push(format!("end {}", name)));
+        name.to_owned()
+    }
+
+    #[derive(Debug, AsyncDefault, PartialEq, Eq)]
+    struct Service {
+        #[default(async = open("pool").await)]
+        pool: String,
+        #[default = 8]
+        workers: usize,
+        #[default(async = open("file").await)]
+        file: String,
+    }
+
+    assert_eq!(
+        Service {
+            pool: "pool".to_owned(),
+            workers: 8,
+            file: "file".to_owned(),
+        },
+        block_on(Service::default_async()),
+    );
+    assert_eq!(
+        LOG.with(|log| log.take()),
+        ["start pool", "start file", "end pool", "end file"],
+    );
+}
+
+#[test]
+fn test_derive_async_default_enum() {
+    use fancy_default::AsyncDefault;
+
+    #[derive(Debug, AsyncDefault, PartialEq, Eq)]
+    enum Handle {
+        Closed,
+        #[default]
+        Open(#[default(async = async { 3 }.await)] u8, u8),
+    }
+
+    assert_eq!(Handle::Open(3, 0), block_on(Handle::default_async()));
+}
+
+#[test]
+fn test_default_provider() {
+    use fancy_default::{Default, DefaultProvider, DefaultSchema, WithDefault};
+
+    struct RegistryPort;
+
+    impl DefaultProvider<u16> for RegistryPort {
+        fn provide() -> u16 {
+            5432
+        }
+    }
+
+    impl DefaultProvider<String> for RegistryPort {
+        fn provide() -> String {
+            "5432".to_owned()
+        }
+    }
+
+    #[derive(Debug, Default, DefaultSchema, PartialEq, Eq)]
+    struct Database {
+        #[default(provider = RegistryPort)]
+        port: u16,
+        #[default(provider = RegistryPort)]
+        port_name: String,
+        fallback: WithDefault<u16, RegistryPort>,
+    }
+
+    assert_eq!(
+        Database {
+            port: 5432,
+            port_name: "5432".to_owned(),
+            fallback: WithDefault::new(5432),
+        },
+        Database::default(),
+    );
+    assert_eq!(
+        "<RegistryPort as DefaultProvider<_>>::provide()",
+        Database::default_schema()[0].expr,
+    );
+
+    fn provided<P: DefaultProvider<u16>>() -> u16 {
+        WithDefault::<u16, P>::default().into_inner()
+    }
+    assert_eq!(5432, provided::<RegistryPort>());
+
+    let mut port: WithDefault<u16, RegistryPort> = 80.into();
+    *port += 1;
+    assert_eq!(81, *port);
+    assert_eq!("81", format!("{:?}", port));
+}
+
+#[test]
+fn test_derive_default_partial() {
+    use fancy_default::{DefaultPartial, Partial};
+
+    #[derive(Debug, DefaultPartial, PartialEq, Eq)]
+    #[default(partial(derive(Debug, Clone)))]
+    struct Server {
+        #[default = 8080]
+        port: u16,
+        #[default(expr = "localhost".to_owned())]
+        host: String,
+    }
+
+    #[derive(Debug, DefaultPartial, PartialEq, Eq)]
+    #[default(partial(derive(Debug, Clone)))]
+    struct Config {
+        #[default(nested(port = 80))]
+        server: Server,
+        #[default = 4]
+        workers: usize,
+        verbose: bool,
+    }
+
+    let file = ConfigPartial {
+        server: ServerPartial {
+            host: Some("example.com".to_owned()),
+            ..Default::default()
+        },
+        workers: Some(8),
+        ..Default::default()
+    };
+    let env = ConfigPartial {
+        workers: Some(16),
+        ..Default::default()
+    };
+    let cli = ConfigPartial {
+        verbose: Some(true),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        Config {
+            server: Server {
+                port: 80,
+                host: "example.com".to_owned(),
+            },
+            workers: 16,
+            verbose: true,
+        },
+        file.clone().merge(env).merge(cli).resolve(),
+    );
+    assert_eq!(
+        Config {
+            server: Server {
+                port: 80,
+                host: "localhost".to_owned(),
+            },
+            workers: 4,
+            verbose: false,
+        },
+        ConfigPartial::default().resolve(),
+    );
+
+    fn layered<T: DefaultPartial>(layers: Vec<T::Partial>) -> T {
+        layers
+            .into_iter()
+            .fold(T::Partial::default(), Partial::merge)
+            .resolve()
+    }
+    let config: Config = layered(vec![file]);
+    assert_eq!(8, config.workers);
+
+    #[derive(Debug, DefaultPartial, PartialEq, Eq)]
+    struct Pair(#[default = 1] u8, u8);
+
+    assert_eq!(Pair(1, 2), PairPartial(None, Some(2)).resolve());
+    assert_eq!(Pair(3, 0), PairPartial(Some(3), None).apply(Pair(1, 0)));
+}
+
+#[test]
+fn test_derive_merge_defaults() {
+    use fancy_default::{Default, MergeDefaults};
+
+    #[derive(Debug, Default, MergeDefaults, PartialEq, Eq)]
+    struct Server {
+        #[default = 8080]
+        port: u16,
+        #[default(expr = "localhost".to_owned())]
+        host: String,
+    }
+
+    #[derive(Debug, Default, MergeDefaults, PartialEq, Eq)]
+    struct Config {
+        #[default(nested)]
+        server: Server,
+        #[default = 4]
+        workers: usize,
+        verbose: bool,
+    }
+
+    let mut config = Config {
+        server: Server {
+            port: 80,
+            host: "example.com".to_owned(),
+        },
+        workers: 8,
+        verbose: true,
+    };
+    config.merge_non_default(Config {
+        server: Server {
+            port: 443,
+            ..Default::default()
+        },
+        workers: 4,
+        ..Default::default()
+    });
+
+    assert_eq!(
+        Config {
+            server: Server {
+                port: 443,
+                host: "example.com".to_owned(),
+            },
+            workers: 8,
+            verbose: true,
+        },
+        config,
+    );
+}
+
+#[test]
+fn test_derive_diff_default() {
+    use fancy_default::{DebugNonDefault, DiffDefault};
+
+    #[derive(fancy_default::Default, DiffDefault, DebugNonDefault, PartialEq, Eq)]
+    struct Server {
+        #[default = 8080]
+        port: u16,
+        #[default(expr = "localhost".to_owned())]
+        host: String,
+    }
+
+    #[derive(DiffDefault, DebugNonDefault, PartialEq, Eq)]
+    struct Config {
+        #[default(nested)]
+        server: Server,
+        #[default = 4]
+        workers: usize,
+        verbose: bool,
+    }
+
+    let config = Config {
+        server: Server {
+            port: 443,
+            host: "localhost".to_owned(),
+        },
+        workers: 4,
+        verbose: true,
+    };
+
+    let diffs = config
+        .non_default_fields()
+        .map(|diff| {
+            format!(
+                "{}: {:?} (default: {:?})",
+                diff.path, diff.value, diff.default
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        diffs,
+        [
+            "server.port: 443 (default: 8080)",
+            "verbose: true (default: false)",
+        ],
+    );
+
+    assert_eq!(
+        "Config { server: Server { port: 443, .. }, verbose: true, .. }",
+        format!("{:?}", config),
+    );
+
+    let config = Config {
+        server: Server {
+            port: 8080,
+            host: "localhost".to_owned(),
+        },
+        workers: 8,
+        verbose: false,
+    };
+    assert_eq!("Config { workers: 8, .. }", format!("{:?}", config));
+
+    #[derive(DebugNonDefault, PartialEq, Eq)]
+    struct Pair(#[default = 1] u8, u8);
+
+    assert_eq!("Pair(2, 3)", format!("{:?}", Pair(2, 3)));
+    assert_eq!("Pair(.., 3)", format!("{:?}", Pair(1, 3)));
+}
+
+#[test]
+fn test_derive_diff_default_nested_overrides() {
+    use fancy_default::{DebugNonDefault, DiffDefault};
+
+    #[derive(fancy_default::Default, DiffDefault, DebugNonDefault, PartialEq, Eq)]
+    struct Server {
+        #[default = 8080]
+        port: u16,
+        #[default(expr = "localhost".to_owned())]
+        host: String,
+    }
+
+    #[derive(fancy_default::Default, DiffDefault, DebugNonDefault, PartialEq, Eq)]
+    struct Config {
+        #[default(nested(port = 80))]
+        server: Server,
+        #[default = 4]
+        workers: usize,
+    }
+
+    let config = Config::default();
+    assert_eq!(0, config.non_default_fields().count());
+    assert_eq!("Config { .. }", format!("{:?}", config));
+
+    let config = Config {
+        server: Server {
+            port: 8080,
+            host: "example.com".to_owned(),
+        },
+        workers: 4,
+    };
+    let diffs = config
+        .non_default_fields()
+        .map(|diff| {
+            format!(
+                "{}: {:?} (default: {:?})",
+                diff.path, diff.value, diff.default
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        diffs,
+        [
+            "server.port: 8080 (default: 80)",
+            r#"server.host: "example.com" (default: "localhost")"#,
+        ],
+    );
+}
+
+#[test]
+fn test_derive_diff_default_nested_expr() {
+    use fancy_default::DiffDefault;
+
+    #[derive(Debug, fancy_default::Default, DiffDefault, PartialEq, Eq)]
+    struct Inner {
+        a: u8,
+    }
+
+    #[derive(fancy_default::Default, DiffDefault)]
+    struct Outer {
+        #[default(expr = Inner { a: 10 }, nested)]
+        inner: Inner,
+    }
+
+    // the declared default of the field is not a difference
+    assert_eq!(0, Outer::default().non_default_fields().count());
+
+    let outer = Outer {
+        inner: Inner { a: 0 },
+    };
+    let diffs = outer
+        .non_default_fields()
+        .map(|diff| format!("{}: {:?} (default: {:?})", diff.path, diff.value, diff.default))
+        .collect::<Vec<_>>();
+    assert_eq!(diffs, ["inner.a: 0 (default: 10)"]);
+}
+
+#[test]
+fn test_derive_deserialize_borrowed() {
+    #[derive(Debug, fancy_default::Deserialize, PartialEq, Eq)]
+    struct Borrowed<'a> {
+        #[default = "anonymous"]
+        name: &'a str,
+        #[default = 1]
+        level: u8,
+    }
+
+    let json = String::from(r#"{"name":"admin"}"#);
+    let borrowed: Borrowed<'_> = serde_json::from_str(&json).unwrap();
+    assert_eq!(
+        Borrowed {
+            name: "admin",
+            level: 1,
+        },
+        borrowed,
+    );
+    assert_eq!("anonymous", serde_json::from_str::<Borrowed>("{}").unwrap().name);
+}
+
+#[test]
+fn test_derive_deserialize() {
+    #[derive(Debug, fancy_default::Default, fancy_default::Deserialize, PartialEq, Eq)]
+    #[default(rename_all = "camelCase")]
+    struct Server {
+        #[default = 8080]
+        listen_port: u16,
+        #[default(expr = "localhost".to_owned(), rename = "hostname")]
+        host: String,
+    }
+
+    #[derive(Debug, fancy_default::Deserialize, PartialEq, Eq)]
+    #[default(deny_unknown_fields)]
+    struct Config {
+        #[default(nested(listen_port = 80))]
+        server: Server,
+        #[default = 4]
+        workers: usize,
+        #[default(required)]
+        name: String,
+    }
+
+    let config: Config = serde_json::from_str(
+        r#"{ "name": "app", "server": { "hostname": "example.com", "extra": 1 } }"#,
+    )
+    .unwrap();
+    assert_eq!(
+        Config {
+            server: Server {
+                listen_port: 8080,
+                host: "example.com".to_owned(),
+            },
+            workers: 4,
+            name: "app".to_owned(),
+        },
+        config,
+    );
+
+    let config: Config = serde_json::from_str(r#"{ "name": "app", "workers": 8 }"#).unwrap();
+    assert_eq!(80, config.server.listen_port);
+    assert_eq!(8, config.workers);
+
+    let error = serde_json::from_str::<Config>(r#"{ "workers": 8 }"#).unwrap_err();
+    assert_eq!("missing field `name` at line 1 column 16", error.to_string());
+    let error = serde_json::from_str::<Config>(r#"{ "name": "app", "port": 8 }"#).unwrap_err();
+    assert!(error.to_string().starts_with("unknown field `port`"));
+}
+
+#[test]
+fn test_derive_deserialize_field_index_and_bytes() {
+    use serde::de::value::{BytesDeserializer, Error, MapDeserializer};
+    use serde::de::IntoDeserializer;
+    use serde::Deserialize;
+
+    #[derive(Debug, fancy_default::Deserialize, PartialEq, Eq)]
+    struct Ports {
+        #[default = 80]
+        http: u16,
+        #[default = 443]
+        https: u16,
+    }
+
+    #[derive(Debug, fancy_default::Deserialize, PartialEq, Eq)]
+    #[default(deny_unknown_fields)]
+    struct Strict {
+        #[default = 1]
+        value: u16,
+    }
+
+    fn from_keys<'de, T: Deserialize<'de>, K: IntoDeserializer<'de, Error>>(
+        entries: Vec<(K, u16)>,
+    ) -> Result<T, Error> {
+        T::deserialize(MapDeserializer::new(entries.into_iter()))
+    }
+
+    struct Bytes(&'static [u8]);
+
+    impl<'de> IntoDeserializer<'de, Error> for Bytes {
+        type Deserializer = BytesDeserializer<'de, Error>;
+
+        fn into_deserializer(self) -> Self::Deserializer {
+            BytesDeserializer::new(self.0)
+        }
+    }
+
+    let ports: Ports = from_keys(vec![(1u64, 8443), (5u64, 1)]).unwrap();
+    assert_eq!(Ports { http: 80, https: 8443 }, ports);
+    let ports: Ports = from_keys(vec![(Bytes(b"http"), 8080), (Bytes(b"\xff"), 1)]).unwrap();
+    assert_eq!(Ports { http: 8080, https: 443 }, ports);
+
+    let strict: Strict = from_keys(vec![(0u64, 2)]).unwrap();
+    assert_eq!(Strict { value: 2 }, strict);
+    let error = from_keys::<Strict, _>(vec![(1u64, 2)]).unwrap_err();
+    assert_eq!(
+        "invalid value: integer `1`, expected field index 0 <= i < 1",
+        error.to_string(),
+    );
+    let error = from_keys::<Strict, _>(vec![(Bytes(b"\xff"), 2)]).unwrap_err();
+    assert!(error.to_string().starts_with("invalid value: byte array"));
+}
+
+#[test]
+fn test_derive_serialize() {
+    #[derive(Debug, fancy_default::Serialize, fancy_default::Deserialize, PartialEq, Eq)]
+    #[default(rename_all = "kebab-case")]
+    struct Config {
+        #[default = 8080]
+        listen_port: u16,
+        #[default(expr = "localhost".to_owned())]
+        host: String,
+        #[default(required)]
+        name: String,
+    }
+
+    #[derive(fancy_default::Serialize)]
+    #[default(skip_defaults = false)]
+    struct Verbose {
+        #[default = 8080]
+        port: u16,
+    }
+
+    let config = Config {
+        listen_port: 80,
+        host: "localhost".to_owned(),
+        name: "app".to_owned(),
+    };
+    let json = serde_json::to_string(&config).unwrap();
+    assert_eq!(r#"{"listen-port":80,"name":"app"}"#, json);
+    assert_eq!(config, serde_json::from_str(&json).unwrap());
+
+    assert_eq!(
+        r#"{"port":8080}"#,
+        serde_json::to_string(&Verbose { port: 8080 }).unwrap(),
+    );
+}
+
+#[test]
+fn test_derive_json_schema_defaults() {
+    use schemars::{schema_for, JsonSchema};
+    use serde_json::json;
+
+    #[derive(fancy_default::JsonSchemaDefaults, JsonSchema, serde::Serialize)]
+    #[schemars(transform = fancy_default::schemars::add_defaults::<Self>)]
+    enum Level {
+        Debug,
+        #[default]
+        Info,
+    }
+
+    #[derive(fancy_default::JsonSchemaDefaults, JsonSchema)]
+    #[schemars(transform = fancy_default::schemars::add_defaults::<Self>)]
+    #[default(rename_all = "kebab-case")]
+    #[serde(rename_all = "kebab-case")]
+    struct Config {
+        #[default = 8080]
+        listen_port: u16,
+        #[default(expr = Level::Debug)]
+        level: Level,
+        #[default(required)]
+        name: String,
+    }
+
+    let schema = schema_for!(Config);
+    assert_eq!(Some(&json!(8080)), schema.pointer("/properties/listen-port/default"));
+    assert_eq!(Some(&json!("Debug")), schema.pointer("/properties/level/default"));
+    assert_eq!(None, schema.pointer("/properties/name/default"));
+    assert_eq!(Some(&json!(["name"])), schema.get("required"));
+    assert_eq!(Some(&json!("Info")), schema.pointer("/$defs/Level/default"));
+}
+
+#[test]
+fn test_derive_apply_overrides() {
+    use fancy_default::traits::OverrideError;
+    use fancy_default::{ApplyOverrides, Default};
+
+    #[derive(Debug, Default, ApplyOverrides, PartialEq, Eq)]
+    struct Tls {
+        #[default = 443]
+        port: u16,
+        enabled: bool,
+    }
+
+    #[derive(Debug, Default, ApplyOverrides, PartialEq, Eq)]
+    struct Server {
+        #[default(nested)]
+        tls: Tls,
+        #[default = 8080]
+        listen_port: u16,
+    }
+
+    #[derive(Debug, Default, ApplyOverrides, PartialEq, Eq)]
+    struct Config {
+        #[default(nested)]
+        server: Server,
+        #[default = 4]
+        workers: usize,
+    }
+
+    let mut config = Config::default();
+    config.apply("server.tls.port", "8443").unwrap();
+    config
+        .apply_env(
+            "APP_",
+            [
+                ("APP_SERVER__LISTEN_PORT", "80"),
+                ("APP_WORKERS", "8"),
+                ("HOME", "/root"),
+            ],
+        )
+        .unwrap();
+    config
+        .apply_args(["--verbose", "-o", "server.tls.enabled=true"])
+        .unwrap();
+
+    assert_eq!(
+        Config {
+            server: Server {
+                tls: Tls {
+                    port: 8443,
+                    enabled: true,
+                },
+                listen_port: 80,
+            },
+            workers: 8,
+        },
+        config,
+    );
+
+    assert_eq!(
+        Err(OverrideError::UnknownKey("server.tls.host".to_owned())),
+        config.apply("server.tls.host", "localhost"),
+    );
+    assert_eq!(
+        "invalid value for `server.tls.port`: invalid digit found in string",
+        config.apply("server.tls.port", "x").unwrap_err().to_string(),
+    );
+    assert_eq!(
+        Err(OverrideError::MalformedArgument("workers".to_owned())),
+        config.apply_args(["-o", "workers"]),
+    );
+}
+
+#[test]
+fn test_setting() {
+    use fancy_default::{ApplyOverrides, ConstDefault, Default, Setting};
+
+    #[derive(Debug, Default, ApplyOverrides, PartialEq, Eq)]
+    struct Server {
+        #[default = 8080]
+        port: Setting<u16>,
+        #[default(expr = "localhost".to_owned())]
+        host: Setting<String>,
+        workers: Setting<usize>,
+        #[default = 3]
+        retries: u8,
+    }
+
+    let mut server = Server::default();
+    assert_eq!(Setting::Default(8080), server.port);
+    assert_eq!(Setting::Default(0), server.workers);
+    assert_eq!(8080, *server.port);
+    assert_eq!(0, server.explicit_fields().count());
+
+    server.host.set("example.com".to_owned());
+    server.apply("port", "8080").unwrap();
+    assert_eq!(Setting::Explicit(8080), server.port);
+    assert_eq!(
+        vec!["port", "host"],
+        server.explicit_fields().collect::<Vec<_>>(),
+    );
+
+    #[derive(Default, serde::Deserialize)]
+    struct Tuple(#[default = 1] Setting<u8>, Setting<u8>);
+
+    let tuple: Tuple = serde_json::from_str("[1, 2]").unwrap();
+    assert!(tuple.0.is_explicit() && tuple.1.is_explicit());
+    assert_eq!(
+        vec!["1"],
+        Tuple(Setting::Default(1), 2.into())
+            .explicit_fields()
+            .collect::<Vec<_>>(),
+    );
+
+    #[derive(ConstDefault)]
+    struct Limits {
+        #[default = 64]
+        max: Setting<u32>,
+    }
+
+    const LIMITS: Limits = Limits::DEFAULT;
+    assert_eq!(0, LIMITS.explicit_fields().count());
+    assert_eq!(
+        vec!["max"],
+        Limits { max: 32.into() }.explicit_fields().collect::<Vec<_>>(),
+    );
+}
+
+#[test]
+fn test_derive_versioned_default() {
+    use fancy_default::{Default, Setting, Version, VersionedDefault};
+
+    #[derive(Debug, Default, VersionedDefault, PartialEq)]
+    struct Cache {
+        #[default(expr = 16, since = "2.0", before = 8)]
+        size: usize,
+        #[default(versions("1.0" = 0.5, "3.1.4" = 0.9, "2" = 0.75))]
+        ratio: f64,
+        #[default(versions("1.0" = 1), before = 0)]
+        shards: Setting<u8>,
+        #[default = 3]
+        retries: u8,
+    }
+
+    #[derive(Debug, Default, VersionedDefault, PartialEq)]
+    struct Config {
+        #[default(nested)]
+        cache: Cache,
+    }
+
+    let newest = Cache {
+        size: 16,
+        ratio: 0.9,
+        shards: Setting::Default(1),
+        retries: 3,
+    };
+    assert_eq!(newest, Cache::default());
+    assert_eq!(
+        newest,
+        Cache::default_for_version(&Version::new(3, 1, 4)),
+    );
+    assert_eq!(
+        Cache {
+            size: 16,
+            ratio: 0.75,
+            shards: Setting::Default(1),
+            retries: 3,
+        },
+        Cache::default_for_version(&"2.5".parse().unwrap()),
+    );
+    assert_eq!(
+        Config {
+            cache: Cache {
+                size: 8,
+                ratio: 0.5,
+                shards: Setting::Default(0),
+                retries: 3,
+            },
+        },
+        Config::default_for_version(&Version::new(0, 9, 0)),
+    );
+
+    assert_eq!(Ok(Version::new(1, 2, 0)), "1.2".parse());
+    assert!("1.2.3.4".parse::<Version>().is_err());
+    assert!("1..2".parse::<Version>().is_err());
+    assert_eq!("1.2.0", Version::new(1, 2, 0).to_string());
+}
+
+#[test]
+fn test_cfg_defaults() {
+    use fancy_default::{defaults, ConstDefault, Default, DefaultSchema};
+
+    #[derive(Debug, Default, ConstDefault, DefaultSchema, PartialEq, Eq)]
+    struct Pool {
+        #[default(cfg(debug_assertions) = 1, cfg(not(debug_assertions)) = 64)]
+        size: usize,
+        #[default(cfg(all(debug_assertions, unix)) = 2, cfg(debug_assertions) = 3, expr = 4)]
+        workers: usize,
+        #[default(cfg(any()) = 5, expr = 6)]
+        retries: usize,
+    }
+
+    let expected = Pool {
+        size: if cfg!(debug_assertions) { 1 } else { 64 },
+        workers: match (cfg!(debug_assertions), cfg!(unix)) {
+            (true, true) => 2,
+            (true, false) => 3,
+            _ => 4,
+        },
+        retries: 6,
+    };
+    assert_eq!(expected, Pool::default());
+    assert_eq!(expected, Pool::DEFAULT);
+    assert_eq!(
+        "cfg(debug_assertions) = 1, cfg(not(debug_assertions)) = 64",
+        Pool::default_schema()[0].expr,
+    );
+    assert_eq!(
+        "cfg(all(debug_assertions, unix)) = 2, cfg(debug_assertions) = 3, expr = 4",
+        Pool::default_schema()[1].expr,
+    );
+    assert_eq!("cfg(any()) = 5, expr = 6", Pool::default_schema()[2].expr);
+
+    #[derive(Debug, Default, ConstDefault, DefaultSchema, PartialEq, Eq)]
+    enum Level {
+        #[default(cfg(debug_assertions))]
+        Verbose,
+        #[default(cfg(not(debug_assertions)))]
+        Quiet,
+    }
+
+    #[defaults(Default)]
+    #[derive(Debug, PartialEq, Eq)]
+    enum Format {
+        #[default(cfg(any()))]
+        Json,
+        #[default(cfg(all()))]
+        Text,
+    }
+
+    let level = if cfg!(debug_assertions) {
+        Level::Verbose
+    } else {
+        Level::Quiet
+    };
+    assert_eq!(level, Level::default());
+    assert_eq!(level, Level::DEFAULT);
+    assert_eq!(Format::Text, Format::default());
+}
+
+#[test]
+fn test_cfg_fields() {
+    use fancy_default::{
+        defaults, ConstDefault, Default, DefaultSchema, TryDefault, VariantDefault,
+    };
+
+    #[derive(Debug, Default, ConstDefault, DefaultSchema, TryDefault, PartialEq, Eq)]
+    struct Derived {
+        #[cfg(feature = "extra")]
+        #[default = 1]
+        extra: u32,
+        #[cfg(not(feature = "extra"))]
+        #[default = 2]
+        fallback: u32,
+        #[default = 3]
+        base: u32,
+    }
+
+    #[defaults(Default, ConstDefault, DefaultSchema)]
+    #[derive(Debug, PartialEq, Eq)]
+    struct Attributed {
+        #[cfg(feature = "extra")]
+        #[default = 1]
+        extra: u32,
+        #[default = 3]
+        base: u32,
+    }
+
+    fancy_default::structure! {
+        #[defaults(Default)]
+        #[derive(Debug, PartialEq, Eq)]
+        struct Inline {
+            #[cfg(feature = "extra")]
+            extra: u32 = 1,
+            base: u32 = 3,
+        }
+    }
+
+    #[derive(Debug, Default, VariantDefault, PartialEq, Eq)]
+    #[variant(const)]
+    enum Mode {
+        #[cfg(feature = "extra")]
+        Extra {
+            #[default = 1]
+            level: u32,
+        },
+        #[default]
+        Base {
+            #[cfg(feature = "extra")]
+            #[default = 1]
+            extra: u32,
+            #[default = 3]
+            base: u32,
+        },
+    }
+
+    #[cfg(feature = "extra")]
+    {
+        let derived = Derived { extra: 1, base: 3 };
+        assert_eq!(derived, Derived::default());
+        assert_eq!(derived, Derived::DEFAULT);
+        assert_eq!(Ok(derived), Derived::try_default());
+        assert_eq!(2, Derived::default_schema().len());
+        assert_eq!(Attributed { extra: 1, base: 3 }, Attributed::default());
+        assert_eq!(Attributed { extra: 1, base: 3 }, Attributed::DEFAULT);
+        assert_eq!(2, Attributed::default_schema().len());
+        assert_eq!(Inline { extra: 1, base: 3 }, Inline::default());
+        assert_eq!(Mode::Extra { level: 1 }, Mode::default_extra());
+        assert_eq!(Mode::Extra { level: 1 }, Mode::EXTRA);
+        assert_eq!(Mode::Base { extra: 1, base: 3 }, Mode::default());
+    }
+    #[cfg(not(feature = "extra"))]
+    {
+        let derived = Derived {
+            fallback: 2,
+            base: 3,
+        };
+        assert_eq!(derived, Derived::default());
+        assert_eq!(derived, Derived::DEFAULT);
+        assert_eq!(Ok(derived), Derived::try_default());
+        assert_eq!(2, Derived::default_schema().len());
+        assert_eq!(Attributed { base: 3 }, Attributed::default());
+        assert_eq!(Attributed { base: 3 }, Attributed::DEFAULT);
+        assert_eq!(1, Attributed::default_schema().len());
+        assert_eq!(Inline { base: 3 }, Inline::default());
+        assert_eq!(Mode::Base { base: 3 }, Mode::default());
+    }
+    assert_eq!(Mode::default(), Mode::default_base());
+    assert_eq!(Mode::default(), Mode::BASE);
+}
+
+#[test]
+fn test_cfg_attribute_traits() {
+    use fancy_default::{defaults, DiffDefault, MergeDefaults, TryDefault};
+
+    #[defaults(
+        Default,
+        TryDefault,
+        DefaultBuilder,
+        DefaultPartial,
+        MergeDefaults,
+        DiffDefault,
+        DebugNonDefault,
+        Serialize,
+        Deserialize
+    )]
+    #[derive(PartialEq, Eq)]
+    struct Settings {
+        #[cfg(feature = "extra")]
+        #[default = 1]
+        extra: u32,
+        #[cfg(not(feature = "extra"))]
+        #[default = 2]
+        fallback: u32,
+        #[default = 3]
+        base: u32,
+    }
+
+    #[defaults(TryDefault)]
+    #[derive(Debug)]
+    struct Limits {
+        #[cfg(feature = "extra")]
+        #[default(try = "5".parse())]
+        extra: u32,
+        #[default(try = "x".parse())]
+        base: u32,
+    }
+
+    #[defaults(Default)]
+    #[derive(Debug, PartialEq, Eq)]
+    enum Mode {
+        #[cfg(feature = "extra")]
+        Extra,
+        #[default]
+        Base {
+            #[cfg(feature = "extra")]
+            #[default = 1]
+            extra: u32,
+            #[default = 3]
+            base: u32,
+        },
+    }
+
+    #[cfg(feature = "extra")]
+    let (settings, mode) = (
+        Settings { extra: 1, base: 3 },
+        Mode::Base { extra: 1, base: 3 },
+    );
+    #[cfg(not(feature = "extra"))]
+    let (settings, mode) = (
+        Settings {
+            fallback: 2,
+            base: 3,
+        },
+        Mode::Base { base: 3 },
+    );
+    assert_eq!(settings, Settings::default());
+    assert_eq!(Ok(settings), Settings::try_default());
+    assert_eq!(Settings::default(), Settings::builder().build());
+    assert_eq!(Settings::default(), SettingsPartial::default().resolve());
+    assert_eq!(mode, Mode::default());
+    let error = Limits::try_default().unwrap_err();
+    assert!(error.base.is_some());
+    #[cfg(feature = "extra")]
+    assert!(error.extra.is_none());
+    #[cfg(feature = "extra")]
+    assert_ne!(Mode::Extra, Mode::default());
+
+    let mut merged = Settings {
+        base: 4,
+        ..Default::default()
+    };
+    merged.merge_non_default(Settings::default());
+    assert_eq!(4, merged.base);
+    assert_eq!(1, merged.non_default_fields().count());
+    assert_eq!(r#"{"base":4}"#, serde_json::to_string(&merged).unwrap());
+    assert_eq!(merged, serde_json::from_str(r#"{"base":4}"#).unwrap());
+}
+
+#[cfg(test)]
+mod prelude {
+    pub use fancy_default as defaults;
+}
+
+/// Defines a struct through the re-export in `prelude`, as a library re-exporting the derives would.
+#[cfg(test)]
+macro_rules! reexported_struct {
+    ($name:ident) => {
+        #[derive(Debug, $crate::prelude::defaults::Default, PartialEq, Eq)]
+        #[default(crate = $crate::prelude::defaults)]
+        struct $name {
+            #[default = 8080]
+            port: u16,
+        }
+    };
+}
+
+#[test]
+fn test_crate_path() {
+    use fancy_default::{version::Version, ConstDefault, Default, DefaultSchema, TryDefault};
+
+    #[derive(Debug, Default, ConstDefault, DefaultSchema, TryDefault, PartialEq, Eq)]
+    #[default(crate = "crate::prelude::defaults")]
+    struct Renamed {
+        #[default = 8080]
+        port: u16,
+        #[default = 3]
+        retries: u8,
+    }
+
+    #[derive(Debug, Default, PartialEq, Eq)]
+    #[default(crate = "crate::prelude::defaults", variant = Tuple)]
+    enum Choice {
+        Plain,
+        Tuple(#[default = 10] usize),
+    }
+
+    #[crate::prelude::defaults::defaults(Default)]
+    #[derive(Debug, PartialEq, Eq)]
+    #[default(crate = crate::prelude::defaults)]
+    struct Attributed {
+        #[default = 1]
+        level: u8,
+    }
+
+    #[derive(Debug, Default, PartialEq, Eq)]
+    #[default(core)]
+    struct Plain {
+        #[default(expr = "localhost".to_owned())]
+        host: String,
+        port: u16,
+        // user code naming the crate is left as written
+        #[default(expr = ::fancy_default::version::Version::new(1, 2, 0))]
+        version: ::fancy_default::version::Version,
+    }
+
+    let renamed = Renamed {
+        port: 8080,
+        retries: 3,
+    };
+    assert_eq!(renamed, Renamed::default());
+    assert_eq!(renamed, Renamed::DEFAULT);
+    assert_eq!(Ok(renamed), Renamed::try_default());
+    assert_eq!(2, Renamed::default_schema().len());
+    assert_eq!(Choice::Tuple(10), Choice::default());
+    assert_eq!(Attributed { level: 1 }, Attributed::default());
+
+    reexported_struct!(Reexported);
+    assert_eq!(Reexported { port: 8080 }, Reexported::default());
+    assert_eq!(
+        Plain {
+            host: "localhost".to_owned(),
+            port: 0,
+            version: Version::new(1, 2, 0),
+        },
+        <Plain as core::default::Default>::default(),
+    );
+}
+
+#[test]
+fn test_derive_merge_defaults_nested_overrides() {
+    use fancy_default::{Default, MergeDefaults};
+
+    #[derive(Debug, Default, MergeDefaults, PartialEq, Eq)]
+    struct Server {
+        #[default = 8080]
+        port: u16,
+        #[default(expr = "localhost".to_owned())]
+        host: String,
+    }
+
+    #[derive(Debug, Default, MergeDefaults, PartialEq, Eq)]
+    struct Config {
+        #[default(nested(port = 80))]
+        server: Server,
+    }
+
+    let mut config = Config {
+        server: Server {
+            port: 9000,
+            host: "example.com".to_owned(),
+        },
+    };
+    config.merge_non_default(Config::default());
+    assert_eq!(9000, config.server.port);
+    assert_eq!("example.com", config.server.host);
+
+    // the default value of the field type differs from the overridden one
+    config.merge_non_default(Config {
+        server: Server {
+            port: 8080,
+            ..Default::default()
+        },
+    });
+    assert_eq!(8080, config.server.port);
+    assert_eq!("example.com", config.server.host);
+}
+
+#[test]
+fn test_derive_merge_defaults_nested_expr() {
+    use fancy_default::{Default, MergeDefaults};
+
+    #[derive(Debug, Default, MergeDefaults, PartialEq, Eq)]
+    struct Inner {
+        a: u8,
+    }
+
+    #[derive(Debug, Default, MergeDefaults, PartialEq, Eq)]
+    struct Outer {
+        #[default(expr = Inner { a: 10 }, nested)]
+        inner: Inner,
+    }
+
+    // the declared default of the field is not merged, unlike the default of its type
+    let mut outer = Outer {
+        inner: Inner { a: 7 },
+    };
+    outer.merge_non_default(Outer::default());
+    assert_eq!(7, outer.inner.a);
+    outer.merge_non_default(Outer {
+        inner: Inner { a: 0 },
+    });
+    assert_eq!(0, outer.inner.a);
+}